@@ -1,6 +1,9 @@
+use searcher::output::OutputOptions;
+use searcher::{Matcher, Options, PatternMatcher};
 use std::env;
 use std::error::Error;
-use std::fs;
+use std::fs::File;
+use std::io::{self, BufReader};
 
 pub struct Config {
     pub query: String,
@@ -33,36 +36,55 @@ impl Config {
     }
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
+// @Note: the search itself is done by the `searcher` crate (see `clair/searcher`),
+// which is shared with `grrs`, so that both match (and print) lines the same way.
 
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
-    } else {
-        search_case_insensitive(&config.query, &contents)
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let options = Options {
+        ignore_case: !config.case_sensitive,
+        ..Options::default()
     };
-
-    for line in results {
-        println!("{}", line);
-    }
+    let matcher = PatternMatcher::new(&config.query, &options)?;
+    let file = File::open(&config.filename)?;
+
+    searcher::search(
+        &matcher,
+        &options,
+        &OutputOptions::default(),
+        BufReader::new(file),
+        None,
+        io::stdout().lock(),
+    )?;
 
     Ok(())
 }
 
-pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
-    contents
+fn search_with<'a>(
+    query: &str,
+    contents: &'a str,
+    options: &Options,
+) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    let matcher = PatternMatcher::new(query, options)?;
+
+    Ok(contents
         .lines()
-        .filter(|line| line.contains(query))
-        .collect()
+        .filter(|line| matcher.is_match(line.as_bytes()))
+        .collect())
 }
 
-pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
-    let query: String = query.to_lowercase();
+pub fn search<'a>(query: &str, contents: &'a str) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    search_with(query, contents, &Options::default())
+}
 
-    contents
-        .lines()
-        .filter(|line| line.to_lowercase().contains(&query))
-        .collect()
+pub fn search_case_insensitive<'a>(
+    query: &str,
+    contents: &'a str,
+) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    let options = Options {
+        ignore_case: true,
+        ..Options::default()
+    };
+    search_with(query, contents, &options)
 }
 
 //
@@ -82,7 +104,10 @@ safe, fast, productive.
 Pick three.
 Duct tape.";
 
-        assert_eq!(vec!["safe, fast, productive."], search(query, contents));
+        assert_eq!(
+            vec!["safe, fast, productive."],
+            search(query, contents).unwrap()
+        );
     }
 
     #[test]
@@ -96,7 +121,22 @@ Trust me.";
 
         assert_eq!(
             vec!["Rust:", "Trust me."],
-            search_case_insensitive(query, contents)
+            search_case_insensitive(query, contents).unwrap()
+        );
+    }
+
+    #[test]
+    fn case_insensitive_unicode() {
+        let query = "ÉCOLE";
+        let contents = "\
+L'école est finie.
+Ecole sans accent.
+ÉCOLE";
+
+        assert_eq!(
+            vec!["L'école est finie.", "ÉCOLE"],
+            search_case_insensitive(query, contents).unwrap()
         );
+        assert_eq!(vec!["ÉCOLE"], search(query, contents).unwrap());
     }
 }
@@ -0,0 +1,68 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use grrs::output::OutputOptions;
+use grrs::{find_matches, search, Options, PatternMatcher};
+use std::io;
+
+// @Note: to run the benchmark, use `cargo bench` (the reports are written to
+// `target/criterion/`).
+
+/// Generates `lines` lines of code-like text, made of pseudo-random identifiers.
+fn generate_text(lines: usize) -> String {
+    let words = [
+        "let", "mut", "self", "match", "config", "reader", "writer", "buffer", "line", "path",
+        "options", "matcher", "result", "error", "context", "offset", "number", "pattern",
+    ];
+
+    // @Note: a linear congruential generator, so that the text is the same on every run.
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (state >> 33) as usize
+    };
+
+    let mut text = String::new();
+    for _ in 0..lines {
+        for _ in 0..8 {
+            text.push_str(words[next() % words.len()]);
+            text.push_str(&format!("_{} ", next() % 1000));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn many_patterns(c: &mut Criterion) {
+    let text = generate_text(20_000);
+    let patterns: Vec<String> = (0..40).map(|i| format!("buffer_{}", i * 25)).collect();
+    let options = Options::default();
+
+    let mut group = c.benchmark_group("40 patterns over 20k lines");
+    group.bench_function("single pass", |b| {
+        b.iter(|| {
+            let matcher = PatternMatcher::with_patterns(&patterns, &options).unwrap();
+            let output = OutputOptions::default();
+            search(
+                &matcher,
+                &options,
+                &output,
+                text.as_bytes(),
+                None,
+                io::sink(),
+            )
+            .unwrap();
+        })
+    });
+    group.bench_function("once per pattern", |b| {
+        b.iter(|| {
+            for pattern in &patterns {
+                find_matches(text.as_bytes(), pattern, &options, io::sink()).unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, many_patterns);
+criterion_main!(benches);
@@ -0,0 +1,106 @@
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// A compression format, which files can be searched through (with `-z`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gzip,
+    Xz,
+    Bzip2,
+    Zstd,
+}
+
+impl Format {
+    /// Detects the format of a file from its extension, e.g. `app.log.gz`.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "gz" | "tgz" => Some(Format::Gzip),
+            "xz" | "txz" => Some(Format::Xz),
+            "bz2" | "tbz2" => Some(Format::Bzip2),
+            "zst" | "zstd" => Some(Format::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Detects the format of a file from the magic bytes at the start of `content`.
+    pub fn from_magic(content: &[u8]) -> Option<Self> {
+        if content.starts_with(&[0x1f, 0x8b]) {
+            Some(Format::Gzip)
+        } else if content.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Format::Xz)
+        } else if matches!(content, [b'B', b'Z', b'h', b'1'..=b'9', ..]) {
+            // @Note: `BZh` is followed by the block size, in hundreds of KB.
+            Some(Format::Bzip2)
+        } else if content.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Format::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// Wraps `reader` into one which decompresses its content, if it's compressed,
+/// as told by its magic bytes or else by the extension of its `path` (if any).
+///
+/// @Note: files made of many compressed streams one after the other (e.g. from
+/// `cat a.gz b.gz`) are decompressed as a whole.
+pub fn decompress<'a>(
+    path: Option<&Path>,
+    mut reader: impl BufRead + 'a,
+) -> io::Result<Box<dyn BufRead + 'a>> {
+    let format =
+        Format::from_magic(reader.fill_buf()?).or_else(|| path.and_then(Format::from_extension));
+
+    Ok(match format {
+        Some(Format::Gzip) => {
+            Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))
+        }
+        Some(Format::Xz) => Box::new(BufReader::new(xz2::bufread::XzDecoder::new_multi_decoder(
+            reader,
+        ))),
+        Some(Format::Bzip2) => {
+            Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader)))
+        }
+        Some(Format::Zstd) => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+        None => Box::new(reader),
+    })
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_formats() {
+        assert_eq!(
+            Format::from_extension(Path::new("logs/app.log.gz")),
+            Some(Format::Gzip)
+        );
+        assert_eq!(
+            Format::from_extension(Path::new("app.log.zst")),
+            Some(Format::Zstd)
+        );
+        assert_eq!(Format::from_extension(Path::new("app.log")), None);
+        assert_eq!(Format::from_extension(Path::new("gz")), None);
+
+        assert_eq!(Format::from_magic(b"BZh91AY&SY"), Some(Format::Bzip2));
+        assert_eq!(
+            Format::from_magic(b"\xfd7zXZ\x00\x00\x04"),
+            Some(Format::Xz)
+        );
+        assert_eq!(Format::from_magic(b"\x1f"), None);
+        assert_eq!(Format::from_magic(b"BZh"), None);
+        assert_eq!(Format::from_magic(b"BZhello"), None);
+        assert_eq!(Format::from_magic(b"plain text"), None);
+
+        // Content which isn't compressed goes through as is.
+        let mut reader = decompress(Some(Path::new("app.log")), &b"plain text"[..]).unwrap();
+        let mut content = String::new();
+        reader.read_line(&mut content).unwrap();
+        assert_eq!(content, "plain text");
+    }
+}
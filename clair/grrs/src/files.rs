@@ -0,0 +1,246 @@
+use crate::decompress::decompress;
+use crate::output::{Mode, OutputOptions};
+use crate::rewrite::{rewrite_file, Rewrite};
+use crate::{search, Matcher, Options};
+use anyhow::{Context, Error, Result};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{WalkBuilder, WalkState};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Which files are searched.
+#[derive(Debug, Clone, Default)]
+pub struct FileOptions {
+    /// Globs of the files to search in directories (every file, when empty).
+    pub include: Vec<String>,
+    /// Globs of the files (and directories) to skip in directories.
+    pub exclude: Vec<String>,
+    /// Search binary files too, instead of skipping them.
+    pub binary: bool,
+    /// Search compressed files through their decompressed content.
+    pub decompress: bool,
+    /// Number of threads searching directories (0 picks it from the number of CPUs).
+    pub threads: usize,
+    /// Rewrite the files with the matches replaced, instead of printing them.
+    pub rewrite: Option<Rewrite>,
+}
+
+/// The outcome of searching many files.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub selected_lines: u64,
+    /// Files listed as having no selected lines (with `Mode::FilesWithoutMatch`).
+    pub files_without_match: u64,
+    /// Errors of the files which couldn't be searched, since they don't stop the
+    /// search of the other ones.
+    pub errors: Vec<Error>,
+}
+
+/// Path which stands for the standard input.
+const STDIN: &str = "-";
+
+/// Whether the content of a file looks binary, i.e. has a NUL byte near its start
+/// (in the reader's first buffer), as `grep` does it.
+fn is_binary(reader: &mut impl BufRead) -> io::Result<bool> {
+    Ok(reader.fill_buf()?.contains(&0))
+}
+
+/// What's searched for, and how, in each file.
+struct Search<'a, M> {
+    matcher: &'a M,
+    options: &'a Options,
+    output: &'a OutputOptions,
+    file_options: &'a FileOptions,
+}
+
+impl<M: Matcher + Sync> Search<'_, M> {
+    /// Searches a file, returning its number of selected lines, or `None` if it
+    /// was skipped as binary.
+    fn file(&self, path: &Path, writer: impl Write) -> Result<Option<u64>> {
+        let is_stdin = path == Path::new(STDIN);
+        if is_stdin && self.file_options.rewrite.is_some() {
+            anyhow::bail!("the standard input can't be rewritten");
+        }
+
+        let mut reader: Box<dyn BufRead> = if is_stdin {
+            Box::new(io::stdin().lock())
+        } else {
+            let file = File::open(path)
+                .with_context(|| format!("could not read file `{}`", path.display()))?;
+            Box::new(BufReader::new(file))
+        };
+
+        let context = || format!("could not search file `{}`", path.display());
+        if self.file_options.decompress {
+            reader = decompress(Some(path).filter(|_| !is_stdin), reader).with_context(context)?;
+        }
+        if !self.file_options.binary && is_binary(&mut reader).with_context(context)? {
+            return Ok(None);
+        }
+
+        if let Some(rewrite) = &self.file_options.rewrite {
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content).with_context(context)?;
+            return rewrite_file(
+                self.matcher,
+                rewrite,
+                self.output.max_count,
+                path,
+                &content,
+                writer,
+            )
+            .map(Some);
+        }
+
+        let label = if is_stdin {
+            Path::new("(standard input)")
+        } else {
+            path
+        };
+        search(
+            self.matcher,
+            self.options,
+            self.output,
+            reader,
+            Some(label),
+            writer,
+        )
+        .map(Some)
+        .with_context(context)
+    }
+
+    /// Adds the outcome of searching a file to `summary`.
+    fn record(&self, summary: &mut Summary, result: Result<Option<u64>>) {
+        match result {
+            Ok(Some(selected_lines)) => {
+                summary.selected_lines += selected_lines;
+                // @Note: the file's path was printed by `Printer::finish`.
+                if selected_lines == 0
+                    && self.output.mode == Mode::FilesWithoutMatch
+                    && self.file_options.rewrite.is_none()
+                {
+                    summary.files_without_match += 1;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => summary.errors.push(e),
+        }
+    }
+
+    fn directory<W: Write + Send>(
+        &self,
+        directory: &Path,
+        overrides: &Override,
+        writer: &Mutex<W>,
+        summary: &Mutex<Summary>,
+    ) {
+        let mut builder = WalkBuilder::new(directory);
+        builder
+            .overrides(overrides.clone())
+            .require_git(false)
+            .threads(self.file_options.threads);
+        // @Note: backups are written while the directory is still being searched, so
+        // they're skipped, not to be rewritten (and backed up) in turn.
+        let backup_suffix = self
+            .file_options
+            .rewrite
+            .as_ref()
+            .filter(|rewrite| !rewrite.dry_run)
+            .and_then(|rewrite| rewrite.backup_suffix.clone());
+        if let Some(suffix) = backup_suffix {
+            builder.filter_entry(move |entry| {
+                !(entry.file_type().is_some_and(|t| t.is_file())
+                    && entry.file_name().to_string_lossy().ends_with(&suffix))
+            });
+        }
+        let walker = builder.build_parallel();
+
+        walker.run(|| {
+            Box::new(|entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        summary.lock().unwrap().errors.push(e.into());
+                        return WalkState::Continue;
+                    }
+                };
+                if !entry.file_type().is_some_and(|t| t.is_file()) {
+                    return WalkState::Continue;
+                }
+
+                // @Note: files are searched in parallel, so each one's output is buffered
+                // and written at once, to keep it from interleaving with other files'.
+                let mut output = Vec::new();
+                let result = self
+                    .file(entry.path(), &mut output)
+                    .and_then(|selected_lines| {
+                        let mut writer = writer.lock().unwrap();
+                        writer
+                            .write_all(&output)
+                            .context("failed to write the output")?;
+                        Ok(selected_lines)
+                    });
+
+                self.record(&mut summary.lock().unwrap(), result);
+                WalkState::Continue
+            })
+        });
+    }
+}
+
+fn overrides(file_options: &FileOptions) -> Result<Override> {
+    let mut builder = OverrideBuilder::new(".");
+    for glob in &file_options.include {
+        builder
+            .add(glob)
+            .with_context(|| format!("invalid glob `{}`", glob))?;
+    }
+    for glob in &file_options.exclude {
+        builder
+            .add(&format!("!{}", glob))
+            .with_context(|| format!("invalid glob `{}`", glob))?;
+    }
+    Ok(builder.build()?)
+}
+
+/// Searches every path in `paths` (where `-` is the standard input), in order,
+/// writing the matches to `writer`.
+///
+/// Directories are searched recursively, and in parallel, skipping hidden files
+/// and the ones ignored by `.gitignore` or `.ignore` files (or by the globs in
+/// `file_options`). Files given explicitly are always searched, as they're read.
+///
+/// Returns how many lines were selected (and files listed without any), and why
+/// files couldn't be searched.
+pub fn search_paths<M: Matcher + Sync, W: Write + Send>(
+    paths: &[PathBuf],
+    matcher: &M,
+    options: &Options,
+    output: &OutputOptions,
+    file_options: &FileOptions,
+    writer: &mut W,
+) -> Result<Summary> {
+    let overrides = overrides(file_options)?;
+    let search = Search {
+        matcher,
+        options,
+        output,
+        file_options,
+    };
+
+    let mut summary = Mutex::new(Summary::default());
+    for path in paths {
+        if path.is_dir() {
+            let shared_writer = Mutex::new(&mut *writer);
+            search.directory(path, &overrides, &shared_writer, &summary);
+            continue;
+        }
+
+        let result = search.file(path, &mut *writer);
+        search.record(summary.get_mut().unwrap(), result);
+    }
+
+    Ok(summary.into_inner().unwrap())
+}
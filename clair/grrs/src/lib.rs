@@ -1,14 +1,32 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
+use searcher::output::OutputOptions;
+use std::io::{BufRead, Write};
 
-/// Searches for `pattern` in `content` and writes the lines that match it to `writer`.
-pub fn find_matches(content: &str, pattern: &str, mut writer: impl std::io::Write) -> Result<()> {
-    for (index, line) in content.lines().enumerate() {
-        if line.contains(pattern) {
-            writeln!(writer, "{}", line)
-                .with_context(|| format!("failed to read line #{}", index + 1))?;
-        }
-    }
+// @Note: matching patterns in lines, and printing them, is done by the `searcher`
+// crate, which is shared with `minigrep` (see `book/ch12-grep`).
+pub use searcher::{output, search, Match, Matcher, Options, PatternMatcher};
+
+pub mod decompress;
+pub mod files;
+pub mod rewrite;
 
+/// Searches for `pattern` in the lines read from `reader` and writes the ones
+/// that match it to `writer`, as they're read.
+pub fn find_matches(
+    reader: impl BufRead,
+    pattern: &str,
+    options: &Options,
+    writer: impl Write,
+) -> Result<()> {
+    let matcher = PatternMatcher::new(pattern, options)?;
+    search(
+        &matcher,
+        options,
+        &OutputOptions::default(),
+        reader,
+        None,
+        writer,
+    )?;
     Ok(())
 }
 
@@ -23,7 +41,13 @@ mod tests {
     #[test]
     fn check_answer_validity() {
         let mut result = Vec::new();
-        assert!(find_matches("lorem ipsum\ndolor sit amet", "lorem", &mut result).is_ok());
+        assert!(find_matches(
+            "lorem ipsum\ndolor sit amet".as_bytes(),
+            "lorem",
+            &Options::default(),
+            &mut result
+        )
+        .is_ok());
         assert_eq!(result, b"lorem ipsum\n");
     }
 }
@@ -1,25 +1,305 @@
 use anyhow::{Context, Result};
+use grrs::files::{self, FileOptions};
+use grrs::output::{Mode, OutputOptions};
+use grrs::rewrite::Rewrite;
+use std::fs;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
 use structopt::StructOpt;
 
-/// Search for a pattern in a file and display the lines that contain it.
+/// When to highlight the output with colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorChoice {
+    /// Only when writing to a terminal.
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "`{}` is not a color choice (auto, always or never)",
+                s
+            )),
+        }
+    }
+}
+
+/// Search for a pattern in files and display the lines that contain it.
 #[derive(StructOpt)]
 struct Cli {
-    /// The pattern to look for.
-    pattern: String,
-    /// The path to the file to read.
+    /// The pattern to look for (or the first path, when patterns are given with
+    /// `-e` or `-f`).
+    pattern: Option<String>,
+    /// The paths to the files to read (`-` being the standard input), where
+    /// directories are searched recursively. Defaults to the current directory.
     #[structopt(parse(from_os_str))]
-    path: std::path::PathBuf,
+    paths: Vec<PathBuf>,
+    /// A pattern to look for, where lines matching any of the patterns given are
+    /// selected.
+    #[structopt(
+        short = "e",
+        long = "regexp",
+        value_name = "pattern",
+        number_of_values = 1
+    )]
+    patterns: Vec<String>,
+    /// Read the patterns to look for from a file, one per line.
+    #[structopt(
+        short = "f",
+        long = "file",
+        value_name = "path",
+        parse(from_os_str),
+        number_of_values = 1
+    )]
+    pattern_files: Vec<PathBuf>,
+    /// Treat the patterns as regular expressions.
+    #[structopt(short = "E", long)]
+    regex: bool,
+    /// Treat the patterns as literal strings (the default).
+    #[structopt(short = "F", long, overrides_with = "regex")]
+    fixed_strings: bool,
+    /// Ignore case distinctions in the patterns and the file.
+    #[structopt(short, long)]
+    ignore_case: bool,
+    /// Only match whole words.
+    #[structopt(short, long = "word-regexp")]
+    word: bool,
+    /// Display the lines that don't match, instead.
+    #[structopt(short = "v", long = "invert-match")]
+    invert: bool,
+    /// Only search the files (in directories) that match this glob.
+    #[structopt(long, value_name = "glob", number_of_values = 1)]
+    include: Vec<String>,
+    /// Skip the files and directories (in directories) that match this glob.
+    #[structopt(long, value_name = "glob", number_of_values = 1)]
+    exclude: Vec<String>,
+    /// Prefix each line with its line number.
+    #[structopt(short = "n", long)]
+    line_number: bool,
+    /// Prefix each line with the byte offset of its start in the file.
+    #[structopt(short, long)]
+    byte_offset: bool,
+    /// Display this many lines after each matching line.
+    #[structopt(short = "A", long, value_name = "num")]
+    after_context: Option<usize>,
+    /// Display this many lines before each matching line.
+    #[structopt(short = "B", long, value_name = "num")]
+    before_context: Option<usize>,
+    /// Display this many lines around each matching line.
+    #[structopt(short = "C", long, value_name = "num")]
+    context: Option<usize>,
+    /// When to highlight matches with colors: auto (when writing to a terminal),
+    /// always or never.
+    #[structopt(long, value_name = "when", default_value = "auto")]
+    color: ColorChoice,
+    /// Only display the number of matching lines of each file.
+    #[structopt(short, long)]
+    count: bool,
+    /// Only display the paths of the files with matching lines.
+    #[structopt(short = "l", long)]
+    files_with_matches: bool,
+    /// Only display the paths of the files without matching lines.
+    #[structopt(short = "L", long)]
+    files_without_match: bool,
+    /// Only display the matching parts of the lines, each on its own line.
+    #[structopt(short, long)]
+    only_matching: bool,
+    /// Stop reading a file after this many matching lines.
+    #[structopt(short, long, value_name = "num")]
+    max_count: Option<u64>,
+    /// Display a JSON record per line (with its path, number, byte offset and
+    /// the matches in it) instead.
+    #[structopt(long)]
+    json: bool,
+    /// Display the matches of the lines replaced by this template, where `$1` (or
+    /// `${1}`) stands for the text matched by the first capture group, `$name` for
+    /// the one matched by the group named `name` and `$0` for the whole match.
+    #[structopt(short, long, value_name = "template")]
+    replace: Option<String>,
+    /// Rewrite the files with the matches replaced (as per `--replace`), instead of
+    /// displaying them.
+    #[structopt(long, requires = "replace", conflicts_with_all = &["invert", "search-zip"])]
+    in_place: bool,
+    /// Keep a copy of each file rewritten in place, at its path followed by this
+    /// suffix (e.g. `.orig`). Files with this suffix are skipped in directories.
+    #[structopt(long, value_name = "suffix", requires = "in-place")]
+    backup: Option<String>,
+    /// Display the changes `--in-place` would make to the files, as a unified diff,
+    /// without making them.
+    #[structopt(long, requires = "in-place")]
+    dry_run: bool,
+    /// Search compressed files (gzip, xz, bzip2 or zstd), as told by their
+    /// extension or their first bytes, through their decompressed content.
+    #[structopt(short = "z", long)]
+    search_zip: bool,
+    /// Search binary files as if they were text, instead of skipping them.
+    #[structopt(short = "a", long)]
+    text: bool,
+    /// Prefix each line with its file name (the default when searching many files).
+    #[structopt(short = "H", long)]
+    with_filename: bool,
+    /// Never prefix lines with their file name.
+    #[structopt(long, overrides_with = "with-filename")]
+    no_filename: bool,
+    /// Number of threads searching directories (by default, based on the number of CPUs).
+    #[structopt(short = "j", long, default_value = "0", hide_default_value = true)]
+    threads: usize,
+}
+
+/// Returns the patterns to look for, and the paths to search, since the first
+/// positional argument is a path when patterns are given with `-e` or `-f`.
+fn patterns_and_paths(
+    pattern: Option<String>,
+    mut patterns: Vec<String>,
+    pattern_files: &[PathBuf],
+    mut paths: Vec<PathBuf>,
+) -> Result<(Vec<String>, Vec<PathBuf>)> {
+    for path in pattern_files {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("could not read file `{}`", path.display()))?;
+        patterns.extend(content.lines().map(str::to_owned));
+    }
+
+    match pattern {
+        Some(pattern) if patterns.is_empty() && pattern_files.is_empty() => patterns.push(pattern),
+        Some(path) => paths.insert(0, path.into()),
+        None if patterns.is_empty() && pattern_files.is_empty() => {
+            anyhow::bail!("no pattern to look for was given")
+        }
+        None => {}
+    }
+
+    Ok((patterns, paths))
 }
 
-fn main() -> Result<()> {
-    let args = Cli::from_args();
+/// Searches as per `args`, returning the exit code.
+fn run(args: Cli) -> Result<ExitCode> {
+    let options = grrs::Options {
+        regex: args.regex && !args.fixed_strings,
+        ignore_case: args.ignore_case,
+        whole_word: args.word,
+        invert: args.invert,
+    };
+    let (patterns, paths) =
+        patterns_and_paths(args.pattern, args.patterns, &args.pattern_files, args.paths)?;
+    let matcher = grrs::PatternMatcher::with_patterns(&patterns, &options)?;
+
+    let paths = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths
+    };
+    let file_options = FileOptions {
+        include: args.include,
+        exclude: args.exclude,
+        binary: args.text,
+        decompress: args.search_zip,
+        threads: args.threads,
+        rewrite: if args.in_place {
+            Some(Rewrite {
+                template: args.replace.clone().unwrap_or_default(),
+                backup_suffix: args.backup,
+                dry_run: args.dry_run,
+            })
+        } else {
+            None
+        },
+    };
+
+    // @Note: as with `grep`, listing files takes precedence over counting lines,
+    // which takes precedence over printing them.
+    let mode = if args.files_with_matches {
+        Mode::FilesWithMatches
+    } else if args.files_without_match {
+        Mode::FilesWithoutMatch
+    } else if args.count {
+        Mode::Count
+    } else if args.json {
+        Mode::Json
+    } else if args.only_matching {
+        Mode::OnlyMatching
+    } else {
+        Mode::Lines
+    };
+    let many_files = paths.len() > 1 || paths.iter().any(|path| path.is_dir());
+    let output = OutputOptions {
+        mode,
+        with_filename: if args.no_filename {
+            false
+        } else {
+            args.with_filename || many_files
+        },
+        line_number: args.line_number,
+        byte_offset: args.byte_offset,
+        before_context: args.before_context.or(args.context).unwrap_or(0),
+        after_context: args.after_context.or(args.context).unwrap_or(0),
+        max_count: args.max_count,
+        color: match args.color {
+            ColorChoice::Auto => io::stdout().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        },
+        replace: args.replace,
+    };
+
+    let mut writer = BufWriter::new(io::stdout());
+    let summary = files::search_paths(
+        &paths,
+        &matcher,
+        &options,
+        &output,
+        &file_options,
+        &mut writer,
+    )?;
+    writer.flush().context("failed to write the output")?;
+
+    for e in &summary.errors {
+        eprintln!("Error: {:#}", e);
+    }
+    // @Note: as with `grep`, listing files without matches succeeds when some were
+    // listed, instead of when lines were selected.
+    let found = if output.mode == Mode::FilesWithoutMatch && file_options.rewrite.is_none() {
+        summary.files_without_match > 0
+    } else {
+        summary.selected_lines > 0
+    };
+    Ok(if !summary.errors.is_empty() {
+        ExitCode::from(EXIT_ERROR)
+    } else if found {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_NO_MATCH)
+    })
+}
 
-    // @Todo @Robustness: this will read the whole file into memory,
-    // however large it may be. It could be optimized using `BufReader`.
-    let content = std::fs::read_to_string(&args.path)
-        .with_context(|| format!("could not read file `{}`", args.path.display()))?;
+/// Exit codes (besides 0, when lines were selected or files listed), as the ones of
+/// `grep`.
+const EXIT_NO_MATCH: u8 = 1;
+const EXIT_ERROR: u8 = 2;
 
-    grrs::find_matches(&content, &args.pattern, &mut std::io::stdout())?;
+fn main() -> ExitCode {
+    let args = match Cli::from_args_safe() {
+        Ok(args) => args,
+        Err(e) if e.use_stderr() => {
+            eprintln!("{}", e.message);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        // @Note: e.g. `--help`, which isn't an error.
+        Err(e) => e.exit(),
+    };
 
-    Ok(())
+    run(args).unwrap_or_else(|e| {
+        eprintln!("Error: {:?}", e);
+        ExitCode::from(EXIT_ERROR)
+    })
 }
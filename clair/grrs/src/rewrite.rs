@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use searcher::{trim_line_ending, Matcher};
+use similar::TextDiff;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// How files are rewritten, with the matches of their lines replaced.
+#[derive(Debug, Clone, Default)]
+pub struct Rewrite {
+    /// What the matches are replaced by (see `Matcher::expand`).
+    pub template: String,
+    /// Keep a copy of each rewritten file, at its path followed by this suffix.
+    pub backup_suffix: Option<String>,
+    /// Only print the changes which would be made, as a unified diff, instead.
+    pub dry_run: bool,
+}
+
+/// Returns `content` with the matches of its lines replaced by `template`, in at
+/// most `max_count` lines (if any), and the number of lines with matches.
+pub fn replace_lines(
+    matcher: &impl Matcher,
+    template: &str,
+    mut content: &[u8],
+    max_count: Option<u64>,
+) -> (Vec<u8>, u64) {
+    let mut replaced = Vec::with_capacity(content.len());
+    let mut matched_lines = 0;
+    while !content.is_empty() {
+        let line_len = content
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(content.len(), |i| i + 1);
+        let (line, rest) = content.split_at(line_len);
+        content = rest;
+
+        let text = trim_line_ending(line);
+        if max_count.is_some_and(|max| matched_lines >= max) {
+            replaced.extend_from_slice(line);
+        } else {
+            if matcher.replace(text, template.as_bytes(), &mut replaced) {
+                matched_lines += 1;
+            }
+            // @Note: line endings are kept as they are, be it `\n` or `\r\n`.
+            replaced.extend_from_slice(&line[text.len()..]);
+        }
+    }
+    (replaced, matched_lines)
+}
+
+/// Writes `content` to a temporary file next to `path`, which then replaces it,
+/// so that the file is never seen half written (and keeps its permissions).
+fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    // @Note: symbolic links are followed, instead of being replaced by a file.
+    let path = fs::canonicalize(path)?;
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut file = NamedTempFile::new_in(directory)?;
+    file.write_all(content)?;
+    file.as_file()
+        .set_permissions(fs::metadata(&path)?.permissions())?;
+    file.persist(&path)?;
+    Ok(())
+}
+
+/// Rewrites the file at `path`, whose content is `content`, with the matches of
+/// its lines replaced as per `rewrite` (or prints the changes to `writer`, on a
+/// dry run).
+///
+/// Returns the number of lines with matches.
+pub fn rewrite_file(
+    matcher: &impl Matcher,
+    rewrite: &Rewrite,
+    max_count: Option<u64>,
+    path: &Path,
+    content: &[u8],
+    mut writer: impl Write,
+) -> Result<u64> {
+    let (replaced, matched_lines) = replace_lines(matcher, &rewrite.template, content, max_count);
+    if replaced == content {
+        return Ok(matched_lines);
+    }
+
+    if rewrite.dry_run {
+        let old = String::from_utf8_lossy(content);
+        let new = String::from_utf8_lossy(&replaced);
+        let path = path.to_string_lossy();
+        write!(
+            writer,
+            "{}",
+            TextDiff::from_lines(&old, &new)
+                .unified_diff()
+                .header(&path, &path)
+        )
+        .context("failed to write the output")?;
+        return Ok(matched_lines);
+    }
+
+    if let Some(suffix) = &rewrite.backup_suffix {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(suffix);
+        fs::copy(path, &backup)
+            .with_context(|| format!("could not back up file `{}`", path.display()))?;
+    }
+    write_atomically(path, &replaced)
+        .with_context(|| format!("could not rewrite file `{}`", path.display()))?;
+    Ok(matched_lines)
+}
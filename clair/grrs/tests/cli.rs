@@ -47,3 +47,639 @@ fn empty_string_as_pattern() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Runs grrs with `args` on a file with the given `content`, returning its output
+/// (whether lines were selected or not).
+fn grrs_output(content: &str, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let mut file = NamedTempFile::new()?;
+    write!(file, "{}", content)?;
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.args(args).arg(file.path());
+    let output = cmd.output()?;
+    assert!(matches!(output.status.code(), Some(0) | Some(1)));
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+const FRUITS: &str = "apple pie\nPineapple\nbanana split\ngrape (green)\n";
+
+#[test]
+fn literal_pattern_by_default() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(grrs_output(FRUITS, &["(green)"])?, "grape (green)\n");
+    assert_eq!(grrs_output(FRUITS, &["a.p"])?, "");
+
+    Ok(())
+}
+
+#[test]
+fn regex_pattern() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(FRUITS, &["-E", "^[a-z]+ (pie|split)$"])?,
+        "apple pie\nbanana split\n"
+    );
+    assert_eq!(grrs_output(FRUITS, &["--regex", "n.n"])?, "banana split\n");
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("-E").arg("(unclosed").arg("Cargo.toml");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid pattern"));
+
+    Ok(())
+}
+
+#[test]
+fn fixed_strings_override_regex() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(grrs_output(FRUITS, &["-E", "-F", "n.n"])?, "");
+    assert_eq!(grrs_output(FRUITS, &["-F", "(green)"])?, "grape (green)\n");
+
+    Ok(())
+}
+
+#[test]
+fn case_insensitive() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(grrs_output(FRUITS, &["pine"])?, "");
+    assert_eq!(grrs_output(FRUITS, &["-i", "pine"])?, "Pineapple\n");
+    assert_eq!(
+        grrs_output(FRUITS, &["-i", "-E", "^[AP]"])?,
+        "apple pie\nPineapple\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn whole_words() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(grrs_output(FRUITS, &["-w", "apple"])?, "apple pie\n");
+    assert_eq!(grrs_output(FRUITS, &["-w", "green"])?, "grape (green)\n");
+    assert_eq!(
+        grrs_output(FRUITS, &["-w", "-E", "gr[a-z]+"])?,
+        "grape (green)\n"
+    );
+    assert_eq!(grrs_output(FRUITS, &["-w", "nana"])?, "");
+
+    Ok(())
+}
+
+#[test]
+fn inverted_match() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(FRUITS, &["-v", "apple"])?,
+        "banana split\ngrape (green)\n"
+    );
+    assert_eq!(
+        grrs_output(FRUITS, &["-v", "-i", "-w", "APPLE"])?,
+        "Pineapple\nbanana split\ngrape (green)\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn read_from_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("-i").arg("PIE").arg("-");
+    cmd.with_stdin()
+        .buffer(FRUITS)
+        .assert()
+        .success()
+        .stdout("apple pie\n");
+
+    Ok(())
+}
+
+#[test]
+fn invalid_utf8_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = NamedTempFile::new()?;
+    file.write_all(b"caf\xe9 latte\r\nmocha\n\xff\xfe tea")?;
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("t").arg(file.path());
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"caf\xe9 latte\n\xff\xfe tea\n");
+
+    Ok(())
+}
+
+#[test]
+fn large_generated_input() -> Result<(), Box<dyn std::error::Error>> {
+    // Long enough (~50MB) to go through many reads of the buffered reader.
+    let lines = 1_000_000;
+    let mut file = std::io::BufWriter::new(NamedTempFile::new()?);
+    for i in 0..lines {
+        let word = if i % 1000 == 999 { "needle" } else { "hay" };
+        writeln!(file, "{:07} the quick brown fox finds {}", i, word)?;
+    }
+    let file = file.into_inner()?;
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("needle").arg(file.path());
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout.lines().count(), lines / 1000);
+    assert_eq!(
+        stdout.lines().last(),
+        Some("0999999 the quick brown fox finds needle")
+    );
+
+    Ok(())
+}
+
+/// Runs grrs with `args` in `dir`, returning its output lines, sorted (since
+/// files in directories are searched in parallel).
+fn grrs_lines(
+    dir: &std::path::Path,
+    args: &[&str],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.current_dir(dir).args(args);
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let mut lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_owned)
+        .collect();
+    lines.sort();
+    Ok(lines)
+}
+
+/// Creates a file in `dir` (and its parent directories) with the given `content`.
+fn create_file(dir: &std::path::Path, path: &str, content: &[u8]) -> std::io::Result<()> {
+    let path = dir.join(path);
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    std::fs::write(path, content)
+}
+
+#[test]
+fn multiple_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    create_file(dir.path(), "fruits.txt", FRUITS.as_bytes())?;
+    create_file(dir.path(), "pies.txt", b"apple pie\npecan pie\n")?;
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.current_dir(dir.path())
+        .args(["pie", "fruits.txt", "pies.txt"]);
+    cmd.assert()
+        .success()
+        .stdout("fruits.txt:apple pie\npies.txt:apple pie\npies.txt:pecan pie\n");
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.current_dir(dir.path())
+        .args(["--no-filename", "pie", "fruits.txt", "pies.txt"]);
+    cmd.assert()
+        .success()
+        .stdout("apple pie\napple pie\npecan pie\n");
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.current_dir(dir.path())
+        .args(["-H", "pecan", "pies.txt"]);
+    cmd.assert().success().stdout("pies.txt:pecan pie\n");
+
+    // Files which can't be read don't stop the search of the others.
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.current_dir(dir.path())
+        .args(["pecan", "missing.txt", "pies.txt"]);
+    cmd.assert()
+        .failure()
+        .stdout("pies.txt:pecan pie\n")
+        .stderr(predicate::str::contains(
+            "could not read file `missing.txt`",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn recursive_search_with_ignore_rules() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    create_file(dir.path(), ".gitignore", b"*.log\n")?;
+    create_file(dir.path(), "src/.ignore", b"generated/\n")?;
+    create_file(dir.path(), "notes.txt", b"find the needle\n")?;
+    create_file(dir.path(), "debug.log", b"needle in a log\n")?;
+    create_file(dir.path(), "src/main.rs", b"// needle\nfn main() {}\n")?;
+    create_file(dir.path(), "src/generated/code.rs", b"// needle\n")?;
+    create_file(dir.path(), "src/image.bin", b"\x89needle\x00\x01")?;
+    create_file(dir.path(), ".hidden/notes.txt", b"hidden needle\n")?;
+
+    assert_eq!(
+        grrs_lines(dir.path(), &["needle"])?,
+        vec!["./notes.txt:find the needle", "./src/main.rs:// needle"]
+    );
+    assert_eq!(
+        grrs_lines(dir.path(), &["needle", "src", "-j", "1"])?,
+        vec!["src/main.rs:// needle"]
+    );
+
+    // Binary files are only searched on demand, and ignored files when explicit.
+    assert_eq!(
+        grrs_lines(dir.path(), &["-a", "--no-filename", "needle", "src"])?,
+        vec!["// needle", "\u{fffd}needle\u{0}\u{1}"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        grrs_lines(dir.path(), &["needle", "debug.log"])?,
+        vec!["needle in a log"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn include_and_exclude_globs() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    create_file(dir.path(), "notes.txt", b"needle\n")?;
+    create_file(dir.path(), "src/main.rs", b"needle\n")?;
+    create_file(dir.path(), "src/lib.rs", b"needle\n")?;
+    create_file(dir.path(), "tests/cli.rs", b"needle\n")?;
+
+    assert_eq!(
+        grrs_lines(dir.path(), &["--include", "*.rs", "needle", "."])?,
+        vec![
+            "./src/lib.rs:needle",
+            "./src/main.rs:needle",
+            "./tests/cli.rs:needle"
+        ]
+    );
+    assert_eq!(
+        grrs_lines(
+            dir.path(),
+            &[
+                "--include",
+                "*.rs",
+                "--exclude",
+                "tests",
+                "--exclude",
+                "lib.*",
+                "needle"
+            ]
+        )?,
+        vec!["./src/main.rs:needle"]
+    );
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.current_dir(dir.path())
+        .args(["--include", "src/{", "needle"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid glob"));
+
+    Ok(())
+}
+
+const NUMBERS: &str =
+    "one\ntwo apple\nthree\nfour\nfive\nsix apple apple\nseven\neight\nnine\nten apple\n";
+
+#[test]
+fn line_numbers_and_byte_offsets() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(NUMBERS, &["-n", "apple"])?,
+        "2:two apple\n6:six apple apple\n10:ten apple\n"
+    );
+    assert_eq!(grrs_output(NUMBERS, &["-b", "-v", "e"])?, "20:four\n");
+    assert_eq!(
+        grrs_output("caf\u{e9}\r\nlatte\n", &["-n", "-b", "latte"])?,
+        "2:7:latte\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn context_lines() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(NUMBERS, &["-n", "-C", "1", "apple"])?,
+        "1-one\n2:two apple\n3-three\n--\n5-five\n6:six apple apple\n7-seven\n--\n9-nine\n10:ten apple\n"
+    );
+    assert_eq!(
+        grrs_output(NUMBERS, &["-A", "1", "six"])?,
+        "six apple apple\nseven\n"
+    );
+
+    // Overlapping context is printed once, and `-A`/`-B` take precedence over `-C`.
+    assert_eq!(
+        grrs_output(NUMBERS, &["-n", "-B", "3", "-C", "0", "apple"])?,
+        "1-one\n2:two apple\n3-three\n4-four\n5-five\n6:six apple apple\n7-seven\n8-eight\n9-nine\n10:ten apple\n"
+    );
+    assert_eq!(
+        grrs_output(NUMBERS, &["-n", "-A", "2", "-B", "1", "-E", "^t"])?,
+        "1-one\n2:two apple\n3:three\n4-four\n5-five\n--\n9-nine\n10:ten apple\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn colored_matches() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(NUMBERS, &["--color", "always", "-n", "six"])?,
+        "\x1b[32m6\x1b[0m\x1b[36m:\x1b[0m\x1b[1;31msix\x1b[0m apple apple\n"
+    );
+    assert_eq!(
+        grrs_output(NUMBERS, &["--color=always", "-i", "APPLE"])?
+            .lines()
+            .nth(1),
+        Some("six \x1b[1;31mapple\x1b[0m \x1b[1;31mapple\x1b[0m")
+    );
+
+    // Colors are disabled when not writing to a terminal (as in these tests).
+    assert_eq!(grrs_output(NUMBERS, &["-n", "six"])?, "6:six apple apple\n");
+    assert_eq!(
+        grrs_output(NUMBERS, &["--color", "never", "six"])?,
+        "six apple apple\n"
+    );
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("--color")
+        .arg("sometimes")
+        .arg("six")
+        .arg("Cargo.toml");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not a color choice"));
+
+    Ok(())
+}
+
+#[test]
+fn multiple_patterns() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(FRUITS, &["-e", "pie", "-e", "split"])?,
+        "apple pie\nbanana split\n"
+    );
+    assert_eq!(
+        grrs_output(FRUITS, &["-i", "-w", "-e", "PIE", "--regexp", "grape"])?,
+        "apple pie\ngrape (green)\n"
+    );
+
+    // Patterns from a file (where an empty line matches every line), with the
+    // first positional argument being a path.
+    let mut patterns = NamedTempFile::new()?;
+    writeln!(patterns, "pine\n(green)")?;
+    let mut file = NamedTempFile::new()?;
+    write!(file, "{}", FRUITS)?;
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("-i")
+        .arg("-f")
+        .arg(patterns.path())
+        .arg(file.path());
+    cmd.assert().success().stdout("Pineapple\ngrape (green)\n");
+
+    writeln!(patterns)?;
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("-f")
+        .arg(patterns.path())
+        .arg("-e")
+        .arg("x")
+        .arg(file.path());
+    cmd.assert().success().stdout(FRUITS);
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.arg("-e")
+        .arg("pie")
+        .arg("-E")
+        .arg("-e")
+        .arg("(unclosed");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid pattern `(unclosed`"));
+
+    Ok(())
+}
+
+#[test]
+fn counts_and_file_lists() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    create_file(dir.path(), "fruits.txt", FRUITS.as_bytes())?;
+    create_file(dir.path(), "numbers.txt", NUMBERS.as_bytes())?;
+    create_file(dir.path(), "empty.txt", b"")?;
+    let files = ["fruits.txt", "numbers.txt", "empty.txt"];
+
+    let output = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("grrs")?;
+        cmd.current_dir(dir.path()).args(args).args(files);
+        Ok(String::from_utf8(cmd.output()?.stdout)?)
+    };
+    assert_eq!(
+        output(&["-c", "apple"])?,
+        "fruits.txt:2\nnumbers.txt:3\nempty.txt:0\n"
+    );
+    assert_eq!(
+        output(&["-c", "-m", "2", "apple"])?,
+        "fruits.txt:2\nnumbers.txt:2\nempty.txt:0\n"
+    );
+    assert_eq!(output(&["-l", "-i", "pine"])?, "fruits.txt\n");
+    assert_eq!(output(&["-L", "-i", "pine"])?, "numbers.txt\nempty.txt\n");
+    assert_eq!(output(&["--no-filename", "-c", "-v", "e"])?, "1\n1\n0\n");
+
+    Ok(())
+}
+
+#[test]
+fn only_matching_and_max_count() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(NUMBERS, &["-o", "-n", "-b", "apple"])?,
+        "2:8:apple\n6:34:apple\n6:40:apple\n10:67:apple\n"
+    );
+    assert_eq!(
+        grrs_output(FRUITS, &["-o", "-E", "-e", "[a-z]+ple", "-e", "gr[a-z]+"])?,
+        "apple\nineapple\ngrape\ngreen\n"
+    );
+    assert_eq!(grrs_output(FRUITS, &["-o", "-v", "apple"])?, "");
+
+    // Context after the last selected line is still printed.
+    assert_eq!(grrs_output(NUMBERS, &["-m", "1", "apple"])?, "two apple\n");
+    assert_eq!(
+        grrs_output(NUMBERS, &["-n", "-m", "2", "-A", "1", "apple"])?,
+        "2:two apple\n3-three\n--\n6:six apple apple\n7-seven\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn json_records() -> Result<(), Box<dyn std::error::Error>> {
+    let output = grrs_output(FRUITS, &["--json", "-B", "1", "-e", "pie", "-e", "split"])?;
+    let records: Vec<serde_json::Value> = output
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), 3);
+
+    assert_eq!(records[0]["type"], "match");
+    assert_eq!(records[0]["line_number"], 1);
+    assert_eq!(records[0]["line"], "apple pie");
+    assert_eq!(
+        records[0]["submatches"],
+        serde_json::json!([{"pattern": 0, "start": 6, "end": 9, "text": "pie"}])
+    );
+    assert!(records[0]["path"].as_str().is_some());
+
+    assert_eq!(records[1]["type"], "context");
+    assert_eq!(records[1]["line"], "Pineapple");
+    assert_eq!(records[2]["type"], "match");
+    assert_eq!(records[2]["offset"], 20);
+    assert_eq!(records[2]["submatches"][0]["pattern"], 1);
+
+    Ok(())
+}
+
+#[test]
+fn exit_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = NamedTempFile::new()?;
+    write!(file, "{}", FRUITS)?;
+
+    let code = |args: &[&str]| -> Result<Option<i32>, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("grrs")?;
+        cmd.args(args).arg(file.path());
+        Ok(cmd.output()?.status.code())
+    };
+    assert_eq!(code(&["apple"])?, Some(0));
+    assert_eq!(code(&["cherry"])?, Some(1));
+    assert_eq!(code(&["-c", "cherry"])?, Some(1));
+    assert_eq!(code(&["-L", "cherry"])?, Some(0));
+    assert_eq!(code(&["-L", "apple"])?, Some(1));
+    assert_eq!(code(&["-E", "(unclosed"])?, Some(2));
+    assert_eq!(code(&["--no-such-flag", "apple"])?, Some(2));
+    assert_eq!(code(&["apple", "missing.txt"])?, Some(2));
+
+    Ok(())
+}
+
+const APP_LOG_ERRORS: &str =
+    "3:2026-10-14 09:01:17 ERROR connection reset by peer\n5:2026-10-14 09:03:08 ERROR disk quota exceeded\n";
+
+#[test]
+fn compressed_files() -> Result<(), Box<dyn std::error::Error>> {
+    for fixture in &["app.log.gz", "app.log.xz", "app.log.bz2", "app.log.zst"] {
+        let path = std::path::Path::new("tests/fixtures").join(fixture);
+
+        let mut cmd = Command::cargo_bin("grrs")?;
+        cmd.args(["-z", "-n", "ERROR"]).arg(&path);
+        cmd.assert().success().stdout(APP_LOG_ERRORS);
+
+        // Compressed files look binary, so they're skipped without `-z`.
+        let mut cmd = Command::cargo_bin("grrs")?;
+        cmd.arg("ERROR").arg(&path);
+        cmd.assert().code(1).stdout("");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compressed_files_detected_by_content() -> Result<(), Box<dyn std::error::Error>> {
+    // A rotated log, gzip compressed but without the extension.
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.args(["-z", "-n", "ERROR", "tests/fixtures/app.log.1"]);
+    cmd.assert().success().stdout(APP_LOG_ERRORS);
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.args(["-z", "-c", "ERROR", "-"]);
+    cmd.with_stdin()
+        .path("tests/fixtures/app.log.zst")?
+        .assert()
+        .success()
+        .stdout("2\n");
+
+    // Plain files are searched as usual, even when they start like a compressed one.
+    assert_eq!(grrs_output(FRUITS, &["-z", "pie"])?, "apple pie\n");
+    assert_eq!(
+        grrs_output("BZhello world\n", &["-z", "hello"])?,
+        "BZhello world\n"
+    );
+
+    // A file which isn't what its extension says.
+    let mut file = tempfile::Builder::new().suffix(".gz").tempfile()?;
+    write!(file, "{}", FRUITS)?;
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.args(["-z", "pie"]).arg(file.path());
+    cmd.assert()
+        .code(2)
+        .stderr(predicate::str::contains("could not search file"));
+
+    Ok(())
+}
+
+#[test]
+fn replacements() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        grrs_output(FRUITS, &["-E", "([a-z]+) pie", "-r", "$1 tart"])?,
+        "apple tart\n"
+    );
+    assert_eq!(
+        grrs_output(
+            FRUITS,
+            &["-E", "-o", "-n", "(?P<fruit>[a-z]+)e\\b", "-r", "<$fruit>"]
+        )?,
+        "1:<appl>\n1:<pi>\n2:<ineappl>\n4:<grap>\n"
+    );
+    // Literal patterns can refer to the whole match.
+    assert_eq!(
+        grrs_output(FRUITS, &["-i", "PIE", "-r", "[$0]"])?,
+        "apple [pie]\n"
+    );
+    // Empty matches are between characters, not inside them.
+    assert_eq!(
+        grrs_output("café\n", &["-E", "x*", "-r", "-"])?,
+        "-c-a-f-é-\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rewrite_in_place() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let source = "let a = foo(1);\r\nlet b = bar(2);\nfoo(3)";
+    create_file(dir.path(), "src/main.rs", source.as_bytes())?;
+    create_file(dir.path(), "src/lib.rs", b"fn bar() {}\n")?;
+
+    let rewrite =
+        |args: &[&str]| -> Result<assert_cmd::assert::Assert, Box<dyn std::error::Error>> {
+            let mut cmd = Command::cargo_bin("grrs")?;
+            cmd.current_dir(dir.path())
+                .args(["-E", "foo\\((\\d+)\\)", "-r", "baz($1)", "--in-place"])
+                .args(args);
+            Ok(cmd.assert())
+        };
+
+    // A dry run only shows the changes.
+    rewrite(&["--dry-run", "src"])?.success().stdout(
+        "--- src/main.rs\n+++ src/main.rs\n@@ -1,3 +1,3 @@\n\
+         -let a = foo(1);\r\n+let a = baz(1);\r\n let b = bar(2);\n\
+         -foo(3)\n\\ No newline at end of file\n+baz(3)\n\\ No newline at end of file\n",
+    );
+    let read = |path: &str| std::fs::read_to_string(dir.path().join(path));
+    assert_eq!(read("src/main.rs")?, source);
+
+    // Backups aren't rewritten (nor backed up), even by a later run.
+    create_file(dir.path(), "src/util.rs.orig", b"foo(4)\n")?;
+    rewrite(&["--backup", ".orig", "src"])?.success().stdout("");
+    assert_eq!(read("src/util.rs.orig")?, "foo(4)\n");
+    assert!(!dir.path().join("src/util.rs.orig.orig").exists());
+    assert_eq!(
+        read("src/main.rs")?,
+        "let a = baz(1);\r\nlet b = bar(2);\nbaz(3)"
+    );
+    assert_eq!(read("src/main.rs.orig")?, source);
+    assert!(!dir.path().join("src/lib.rs.orig").exists());
+
+    // There's nothing left to replace.
+    rewrite(&["src/main.rs"])?.code(1);
+
+    let mut cmd = Command::cargo_bin("grrs")?;
+    cmd.args(["foo", "--in-place", "-"]);
+    cmd.assert().code(2).stderr(predicate::str::contains(
+        "required arguments were not provided",
+    ));
+
+    Ok(())
+}
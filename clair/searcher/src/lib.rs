@@ -0,0 +1,571 @@
+use crate::output::{Mode, OutputOptions, Printer};
+use aho_corasick::{AhoCorasick, MatchKind};
+use anyhow::{Context, Result};
+use regex_automata::util::{interpolate, syntax};
+use regex_automata::{meta, Anchored, PatternID};
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::str;
+
+pub mod output;
+
+/// How patterns are matched against each line.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Treat patterns as regular expressions, instead of literal strings.
+    pub regex: bool,
+    pub ignore_case: bool,
+    /// Only match whole words, i.e. not preceded or followed by a word character.
+    pub whole_word: bool,
+    /// Select the lines that don't match, instead.
+    pub invert: bool,
+}
+
+/// Where a pattern matched in a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// Index of the pattern which matched, in the order they were given.
+    pub pattern: usize,
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Engine {
+    /// Literal patterns, all matched in a single pass by an Aho-Corasick automaton.
+    Literals(AhoCorasick),
+    /// Regular expressions, all compiled into a single (multi-pattern) regex.
+    Regexes(meta::Regex),
+}
+
+impl Engine {
+    fn find_at(&self, line: &[u8], start: usize) -> Option<Match> {
+        match self {
+            Engine::Literals(automaton) => {
+                let input = aho_corasick::Input::new(line).span(start..line.len());
+                automaton.find(input).map(|found| Match {
+                    pattern: found.pattern().as_usize(),
+                    range: found.range(),
+                })
+            }
+            Engine::Regexes(regex) => {
+                let input = regex_automata::Input::new(line).span(start..line.len());
+                regex.find(input).map(|found| Match {
+                    pattern: found.pattern().as_usize(),
+                    range: found.range(),
+                })
+            }
+        }
+    }
+
+    fn expand(&self, line: &[u8], found: &Match, template: &[u8], dst: &mut Vec<u8>) {
+        match self {
+            Engine::Literals(_) => expand_whole_match(line, found, template, dst),
+            Engine::Regexes(regex) => {
+                // @Note: searching again for the same pattern, anchored at the start of
+                // the match and ending with it, finds the same match (since look-arounds
+                // still see the whole line), but with its capture groups this time.
+                let input = regex_automata::Input::new(line)
+                    .range(found.range.clone())
+                    .anchored(Anchored::Pattern(PatternID::must(found.pattern)));
+                let mut captures = regex.create_captures();
+                regex.search_captures(&input, &mut captures);
+                captures.interpolate_bytes_into(line, template, dst);
+            }
+        }
+    }
+}
+
+/// Appends `template` to `dst`, where `$0` is replaced by the text of `found`.
+fn expand_whole_match(line: &[u8], found: &Match, template: &[u8], dst: &mut Vec<u8>) {
+    interpolate::bytes(
+        template,
+        |index, dst| {
+            if index == 0 {
+                dst.extend_from_slice(&line[found.range.clone()]);
+            }
+        },
+        |_| None,
+        dst,
+    );
+}
+
+/// Finds where (and which) patterns match in lines.
+///
+/// Lines are matched as bytes, so that they don't need to be valid UTF-8.
+pub trait Matcher {
+    /// Returns the first match in `line` which starts at or after `start`.
+    fn find_at(&self, line: &[u8], start: usize) -> Option<Match>;
+
+    /// Appends `template` to `dst`, where `$1` (or `${1}`) is replaced by the text
+    /// matched by the first capture group of `found`, `$name` by the one matched by
+    /// the group named `name`, and `$0` by the whole match (`$$` being a `$`).
+    ///
+    /// By default, the whole match is the only group.
+    fn expand(&self, line: &[u8], found: &Match, template: &[u8], dst: &mut Vec<u8>) {
+        expand_whole_match(line, found, template, dst);
+    }
+
+    /// Returns every (non-overlapping) match in `line`, in order.
+    fn find_iter<'a>(&'a self, line: &'a [u8]) -> FindIter<'a, Self> {
+        FindIter {
+            matcher: self,
+            line,
+            start: 0,
+            last_end: None,
+        }
+    }
+
+    fn is_match(&self, line: &[u8]) -> bool {
+        self.find_at(line, 0).is_some()
+    }
+
+    /// Appends `line` to `dst`, with each of its matches replaced by `template` (as
+    /// expanded by `expand`), returning whether there were any.
+    fn replace(&self, line: &[u8], template: &[u8], dst: &mut Vec<u8>) -> bool {
+        let mut end = 0;
+        let mut any = false;
+        for found in self.find_iter(line) {
+            dst.extend_from_slice(&line[end..found.range.start]);
+            self.expand(line, &found, template, dst);
+            end = found.range.end;
+            any = true;
+        }
+        dst.extend_from_slice(&line[end..]);
+        any
+    }
+}
+
+/// Iterator over the matches in a line (see `Matcher::find_iter`).
+pub struct FindIter<'a, M: ?Sized> {
+    matcher: &'a M,
+    line: &'a [u8],
+    start: usize,
+    last_end: Option<usize>,
+}
+
+impl<M: Matcher + ?Sized> Iterator for FindIter<'_, M> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        loop {
+            // @Note: the start is past the end of the line after an empty match at
+            // its end, which `find_at` implementations aren't expected to handle.
+            if self.start > self.line.len() {
+                return None;
+            }
+            let found = self.matcher.find_at(self.line, self.start)?;
+            // @Note: empty matches would be found again at the same position, so the
+            // next character is skipped (as a whole, or a single invalid byte).
+            self.start = if found.range.is_empty() {
+                found.range.end
+                    + first_char(&self.line[found.range.end..]).map_or(1, char::len_utf8)
+            } else {
+                found.range.end
+            };
+
+            // @Note: as with `sed`, an empty match right after another match isn't
+            // one, e.g. `a*` matches `baac` at 0..0, 1..3 and 4..4, but not at 3..3.
+            if found.range.is_empty() && self.last_end == Some(found.range.start) {
+                continue;
+            }
+            self.last_end = Some(found.range.end);
+            return Some(found);
+        }
+    }
+}
+
+/// Compiled patterns, literal or regular expressions, all matched in a single pass.
+///
+/// Invalid UTF-8 sequences are never matched by the patterns, nor treated as word
+/// characters.
+#[derive(Debug, Clone)]
+pub struct PatternMatcher {
+    engine: Engine,
+    whole_word: bool,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Decodes the first character of `bytes`, if they start with valid UTF-8.
+fn first_char(bytes: &[u8]) -> Option<char> {
+    let prefix = &bytes[..bytes.len().min(4)];
+    let valid = match str::from_utf8(prefix) {
+        Ok(valid) => valid,
+        Err(e) => str::from_utf8(&prefix[..e.valid_up_to()]).unwrap(),
+    };
+    valid.chars().next()
+}
+
+/// Decodes the last character of `bytes`, if they end with valid UTF-8.
+fn last_char(bytes: &[u8]) -> Option<char> {
+    (bytes.len().saturating_sub(4)..bytes.len())
+        .find_map(|start| str::from_utf8(&bytes[start..]).ok())
+        .and_then(|valid| valid.chars().next_back())
+}
+
+fn build_regexes(patterns: &[String], ignore_case: bool) -> Result<meta::Regex> {
+    // @Note: configured as `regex::bytes::Regex` is, so that patterns can match
+    // invalid UTF-8 (e.g. with `(?-u:\xFF)`).
+    meta::Regex::builder()
+        .syntax(
+            syntax::Config::new()
+                .case_insensitive(ignore_case)
+                .utf8(false),
+        )
+        .configure(meta::Config::new().utf8_empty(false))
+        .build_many(patterns)
+        .map_err(|e| {
+            let pattern = match e.pattern() {
+                Some(id) => patterns[id.as_usize()].clone(),
+                None => patterns.join("`, `"),
+            };
+            anyhow::Error::new(e).context(format!("invalid pattern `{}`", pattern))
+        })
+}
+
+impl PatternMatcher {
+    pub fn new(pattern: &str, options: &Options) -> Result<Self> {
+        Self::with_patterns(&[pattern], options)
+    }
+
+    /// Compiles every pattern in `patterns` into a single matcher, so that lines
+    /// are only scanned once, however many patterns there are.
+    pub fn with_patterns<P: AsRef<str>>(patterns: &[P], options: &Options) -> Result<Self> {
+        let patterns: Vec<&str> = patterns.iter().map(AsRef::as_ref).collect();
+
+        // @Note: the automaton only folds ASCII letters, which is the same as what
+        // the regex engine does for ASCII patterns (but for oddities such as the
+        // Kelvin sign matching `k`), so other patterns are escaped into regexes.
+        let engine =
+            if !options.regex && (!options.ignore_case || patterns.iter().all(|p| p.is_ascii())) {
+                let automaton = AhoCorasick::builder()
+                    .match_kind(MatchKind::LeftmostFirst)
+                    .ascii_case_insensitive(options.ignore_case)
+                    .build(&patterns)
+                    .context("too many patterns")?;
+                Engine::Literals(automaton)
+            } else {
+                let patterns: Vec<String> = patterns
+                    .iter()
+                    .map(|&pattern| {
+                        if options.regex {
+                            pattern.to_owned()
+                        } else {
+                            regex::escape(pattern)
+                        }
+                    })
+                    .collect();
+                Engine::Regexes(build_regexes(&patterns, options.ignore_case)?)
+            };
+
+        Ok(PatternMatcher {
+            engine,
+            whole_word: options.whole_word,
+        })
+    }
+
+    fn is_whole_word(&self, line: &[u8], range: &Range<usize>) -> bool {
+        let before = last_char(&line[..range.start]);
+        let after = first_char(&line[range.end..]);
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    }
+}
+
+impl Matcher for PatternMatcher {
+    fn find_at(&self, line: &[u8], start: usize) -> Option<Match> {
+        let mut start = start;
+        while start <= line.len() {
+            let found = self.engine.find_at(line, start)?;
+            if !self.whole_word || self.is_whole_word(line, &found.range) {
+                return Some(found);
+            }
+
+            // @Note: search again from the next character, since a match starting
+            // later may still be a whole word (shorter matches starting at the same
+            // position aren't tried).
+            let range = found.range;
+            start = range.start + first_char(&line[range.start..]).map_or(1, char::len_utf8);
+        }
+        None
+    }
+
+    fn expand(&self, line: &[u8], found: &Match, template: &[u8], dst: &mut Vec<u8>) {
+        self.engine.expand(line, found, template, dst);
+    }
+}
+
+/// Strips the line ending (`\n` or `\r\n`) off of `line`, if it has one.
+pub fn trim_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Searches the lines read from `reader` with `matcher` and prints the ones that
+/// are selected (as per `options`) to `writer`, as they're read, as per `output`
+/// (where `path` is the one of the file being read, if any).
+///
+/// Returns the number of selected lines.
+pub fn search(
+    matcher: &impl Matcher,
+    options: &Options,
+    output: &OutputOptions,
+    mut reader: impl BufRead,
+    path: Option<&Path>,
+    writer: impl Write,
+) -> Result<u64> {
+    let mut printer = Printer::new(matcher, output, path, writer);
+
+    // @Note: listing a file only takes its first selected line.
+    let max_count = match output.mode {
+        Mode::FilesWithMatches | Mode::FilesWithoutMatch => Some(1),
+        _ => output.max_count,
+    };
+    let (before_context_len, after_context_len) = if output.mode.shows_context() {
+        (output.before_context, output.after_context)
+    } else {
+        (0, 0)
+    };
+
+    // Lines which weren't printed (yet) but may be the context of a selected line,
+    // as `(number, offset, line)`.
+    let mut before_context: VecDeque<(u64, u64, Vec<u8>)> = VecDeque::new();
+    let mut after_context = 0;
+    let mut selected_lines = 0;
+
+    // @Note: the same buffer is reused for every line, so memory use only depends
+    // on the length of the longest line, not on the size of the input.
+    let mut line = Vec::new();
+    let mut number = 0;
+    let mut offset = 0;
+    loop {
+        // @Note: as with `grep`, the context after the last selected line is still
+        // printed once the maximum count is reached.
+        let reached_max_count = max_count.is_some_and(|max| selected_lines >= max);
+        if reached_max_count && after_context == 0 {
+            break;
+        }
+
+        number += 1;
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .with_context(|| format!("failed to read line #{}", number))?;
+        if read == 0 {
+            break;
+        }
+
+        let content = trim_line_ending(&line);
+        let selected = !reached_max_count && matcher.is_match(content) != options.invert;
+        if selected {
+            selected_lines += 1;
+            for (number, offset, content) in before_context.drain(..) {
+                printer
+                    .line(number, offset, &content, false)
+                    .with_context(|| format!("failed to write line #{}", number))?;
+            }
+        }
+        if selected || after_context > 0 {
+            printer
+                .line(number, offset, content, selected)
+                .with_context(|| format!("failed to write line #{}", number))?;
+            after_context = if selected {
+                after_context_len
+            } else {
+                after_context - 1
+            };
+        } else if before_context_len > 0 {
+            if before_context.len() == before_context_len {
+                before_context.pop_front();
+            }
+            before_context.push_back((number, offset, content.to_vec()));
+        }
+
+        offset += read as u64;
+    }
+
+    printer
+        .finish(selected_lines)
+        .context("failed to write the output")?;
+    Ok(selected_lines)
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_matches() {
+        /// Matches an empty string everywhere, slicing the line as it goes.
+        struct Empty;
+
+        impl Matcher for Empty {
+            fn find_at(&self, line: &[u8], start: usize) -> Option<Match> {
+                let start = line.len() - line[start..].len();
+                Some(Match {
+                    pattern: 0,
+                    range: start..start,
+                })
+            }
+        }
+
+        let ranges: Vec<_> = Empty.find_iter("aé".as_bytes()).map(|m| m.range).collect();
+        assert_eq!(ranges, vec![0..0, 1..1, 3..3]);
+    }
+
+    #[test]
+    fn whole_words() {
+        let options = Options {
+            whole_word: true,
+            ..Options::default()
+        };
+        let matcher = PatternMatcher::new("cat", &options).unwrap();
+        let found = matcher.find_at(b"concatenate the cat", 0);
+        assert_eq!(found.map(|found| found.range), Some(16..19));
+        assert!(!matcher.is_match(b"cats"));
+
+        // Words are delimited by non-word characters, whatever the pattern's edges are.
+        let matcher = PatternMatcher::new("-v", &options).unwrap();
+        assert!(matcher.is_match(b"grrs -v x"));
+        assert!(!matcher.is_match(b"grrs a-vx"));
+
+        let matcher = PatternMatcher::new("café", &options).unwrap();
+        assert!(matcher.is_match("un café, s'il vous plaît".as_bytes()));
+        assert!(!matcher.is_match("cafés".as_bytes()));
+        assert!(matcher.is_match(b"\xffcaf\xc3\xa9\xfe"));
+    }
+
+    #[test]
+    fn invalid_utf8_lines() {
+        let find_matches = |content: &[u8], pattern: &str, options: &Options| {
+            let matcher = PatternMatcher::new(pattern, options).unwrap();
+            let mut result = Vec::new();
+            search(
+                &matcher,
+                options,
+                &OutputOptions::default(),
+                content,
+                None,
+                &mut result,
+            )
+            .unwrap();
+            result
+        };
+
+        let content = b"caf\xe9 latte\r\nmocha\n\xff\xfe tea\n";
+        let result = find_matches(&content[..], "t", &Options::default());
+        assert_eq!(result, b"caf\xe9 latte\n\xff\xfe tea\n");
+
+        // Invalid sequences aren't characters, so they're not matched by `.`.
+        let options = Options {
+            regex: true,
+            ..Options::default()
+        };
+        let content = b"caf\xe9 latte\ncaf\xc3\xa9 au lait\n";
+        let result = find_matches(&content[..], "caf. ", &options);
+        assert_eq!(result, "café au lait\n".as_bytes());
+    }
+
+    #[test]
+    fn multiple_patterns() {
+        let found = |matcher: &PatternMatcher, line: &str| -> Vec<(usize, Range<usize>)> {
+            matcher
+                .find_iter(line.as_bytes())
+                .map(|found| (found.pattern, found.range))
+                .collect()
+        };
+
+        let patterns = ["apple", "pie", "pineapple"];
+        let matcher = PatternMatcher::with_patterns(&patterns, &Options::default()).unwrap();
+        assert_eq!(
+            found(&matcher, "pineapple pie"),
+            vec![(2, 0..9), (1, 10..13)]
+        );
+
+        // Earlier patterns are preferred among the ones matching at the same position.
+        let options = Options {
+            ignore_case: true,
+            ..Options::default()
+        };
+        let matcher = PatternMatcher::with_patterns(&["pineapple", "pine"], &options).unwrap();
+        assert_eq!(found(&matcher, "PINEAPPLE"), vec![(0, 0..9)]);
+        let matcher = PatternMatcher::with_patterns(&["crème", "brûlée"], &options).unwrap();
+        assert_eq!(found(&matcher, "CRÈME BRÛLÉE"), vec![(0, 0..6), (1, 7..15)]);
+
+        let options = Options {
+            regex: true,
+            whole_word: true,
+            ..Options::default()
+        };
+        let matcher = PatternMatcher::with_patterns(&["[0-9]+", "p[a-z]+"], &options).unwrap();
+        assert_eq!(
+            found(&matcher, "3 pies for 12x pounds"),
+            vec![(0, 0..1), (1, 2..6), (1, 15..21)]
+        );
+        assert!(PatternMatcher::with_patterns(&["ok", "(unclosed"], &options).is_err());
+    }
+
+    #[test]
+    fn replacements() {
+        let replaced = |matcher: &PatternMatcher, line: &str, template: &str| -> String {
+            let mut result = Vec::new();
+            matcher.replace(line.as_bytes(), template.as_bytes(), &mut result);
+            String::from_utf8(result).unwrap()
+        };
+
+        let options = Options {
+            regex: true,
+            ..Options::default()
+        };
+        let matcher = PatternMatcher::new(r"(\w+)@(?P<host>\w+)\.com", &options).unwrap();
+        assert_eq!(
+            replaced(
+                &matcher,
+                "mail ann@example.com, bob@test.com",
+                "${1} at $host ($$)"
+            ),
+            "mail ann at example ($), bob at test ($)"
+        );
+        // Unknown groups are replaced by nothing.
+        assert_eq!(replaced(&matcher, "a@b.com", "<$2$3$nope>"), "<b>");
+
+        // Groups are the ones of the pattern which was found.
+        let matcher = PatternMatcher::with_patterns(&["(a)", "a(.)"], &options).unwrap();
+        let found = Match {
+            pattern: 1,
+            range: 0..2,
+        };
+        let mut result = Vec::new();
+        matcher.expand(b"ab", &found, b"<$1>", &mut result);
+        assert_eq!(result, b"<b>");
+
+        let matcher = PatternMatcher::new("a*", &options).unwrap();
+        assert_eq!(replaced(&matcher, "baac", "-"), "-b-c-");
+        // Empty matches don't split characters.
+        let matcher = PatternMatcher::new("x*", &options).unwrap();
+        assert_eq!(replaced(&matcher, "café", "-"), "-c-a-f-é-");
+
+        // Groups are the ones of the pattern which matched.
+        let matcher = PatternMatcher::with_patterns(&["(x)(y)", "(a)(b)"], &options).unwrap();
+        assert_eq!(replaced(&matcher, "ab xy", "$2$1"), "ba yx");
+
+        // Literal patterns only have the whole match.
+        let options = Options {
+            ignore_case: true,
+            whole_word: true,
+            ..Options::default()
+        };
+        let matcher = PatternMatcher::new("pie", &options).unwrap();
+        assert_eq!(
+            replaced(&matcher, "Pie pies PIE", "[$0$1]"),
+            "[Pie] pies [PIE]"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{Match, Matcher};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// What is printed for each file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// The selected lines (and their context).
+    #[default]
+    Lines,
+    /// Only the (non-empty) matching parts of the selected lines, one per line.
+    OnlyMatching,
+    /// The number of selected lines.
+    Count,
+    /// The path of the file, if it has selected lines.
+    FilesWithMatches,
+    /// The path of the file, if it has no selected lines.
+    FilesWithoutMatch,
+    /// A JSON record per selected (or context) line.
+    Json,
+}
+
+impl Mode {
+    /// Whether lines are printed, with their context.
+    pub fn shows_context(self) -> bool {
+        matches!(self, Mode::Lines | Mode::Json)
+    }
+}
+
+/// How the selected lines are printed.
+#[derive(Debug, Clone, Default)]
+pub struct OutputOptions {
+    pub mode: Mode,
+    /// Prefix each line with the path of its file.
+    pub with_filename: bool,
+    /// Prefix each line with its (1-based) number.
+    pub line_number: bool,
+    /// Prefix each line with the (0-based) byte offset of its start in the input
+    /// (or of the match's start, when only printing matches).
+    pub byte_offset: bool,
+    /// Number of lines to print before each selected line.
+    pub before_context: usize,
+    /// Number of lines to print after each selected line.
+    pub after_context: usize,
+    /// Stop reading a file after this many selected lines.
+    pub max_count: Option<u64>,
+    /// Highlight matches (and prefixes) with ANSI escape codes.
+    pub color: bool,
+    /// Print the matches of the selected lines replaced by this template (see
+    /// `Matcher::expand`), instead of as they are.
+    pub replace: Option<String>,
+}
+
+const RESET: &str = "\x1b[0m";
+const MATCH_COLOR: &str = "\x1b[1;31m";
+const PATH_COLOR: &str = "\x1b[35m";
+const NUMBER_COLOR: &str = "\x1b[32m";
+const SEPARATOR_COLOR: &str = "\x1b[36m";
+
+/// Part of a line matched by a pattern, in a JSON record.
+#[derive(Serialize)]
+struct JsonSubmatch<'a> {
+    /// Index of the pattern which matched.
+    pattern: usize,
+    /// Byte offsets of the match in the line.
+    start: usize,
+    end: usize,
+    text: Cow<'a, str>,
+}
+
+/// A line, in JSON output, e.g.:
+///
+/// `{"type":"match","path":"src/lib.rs","line_number":3,"offset":42,"line":"...","submatches":[...]}`
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    /// Either `match`, for selected lines, or `context`.
+    #[serde(rename = "type")]
+    kind: &'static str,
+    path: Option<Cow<'a, str>>,
+    line_number: u64,
+    /// Byte offset of the line's start in the input.
+    offset: u64,
+    /// The line, where invalid UTF-8 is replaced by `U+FFFD`.
+    line: Cow<'a, str>,
+    submatches: Vec<JsonSubmatch<'a>>,
+}
+
+/// Prints the lines of a search, as `grep` does, i.e. with their prefixes followed
+/// by `:` (or by `-`, for context lines), and `--` between non-adjacent groups of
+/// lines, when there's context.
+pub struct Printer<'a, M, W> {
+    matcher: &'a M,
+    options: &'a OutputOptions,
+    path: Option<&'a Path>,
+    writer: W,
+    last_number: Option<u64>,
+}
+
+impl<'a, M: Matcher, W: Write> Printer<'a, M, W> {
+    /// Creates a printer for the lines of the file at `path` (if it has one).
+    pub fn new(
+        matcher: &'a M,
+        options: &'a OutputOptions,
+        path: Option<&'a Path>,
+        writer: W,
+    ) -> Self {
+        Printer {
+            matcher,
+            options,
+            path,
+            writer,
+            last_number: None,
+        }
+    }
+
+    fn colored(&mut self, color: &str, text: impl Display) -> io::Result<()> {
+        if self.options.color {
+            write!(self.writer, "{}{}{}", color, text, RESET)
+        } else {
+            write!(self.writer, "{}", text)
+        }
+    }
+
+    fn path(&mut self) -> io::Result<()> {
+        match self.path {
+            Some(path) => self.colored(PATH_COLOR, path.display()),
+            None => Ok(()),
+        }
+    }
+
+    fn prefix(&mut self, number: u64, offset: u64, separator: char) -> io::Result<()> {
+        if self.options.with_filename && self.path.is_some() {
+            self.path()?;
+            self.colored(SEPARATOR_COLOR, separator)?;
+        }
+        if self.options.line_number {
+            self.colored(NUMBER_COLOR, number)?;
+            self.colored(SEPARATOR_COLOR, separator)?;
+        }
+        if self.options.byte_offset {
+            self.colored(NUMBER_COLOR, offset)?;
+            self.colored(SEPARATOR_COLOR, separator)?;
+        }
+        Ok(())
+    }
+
+    fn matched(&mut self, text: &[u8]) -> io::Result<()> {
+        if self.options.color && !text.is_empty() {
+            self.writer.write_all(MATCH_COLOR.as_bytes())?;
+            self.writer.write_all(text)?;
+            self.writer.write_all(RESET.as_bytes())
+        } else {
+            self.writer.write_all(text)
+        }
+    }
+
+    /// Prints the match `found` in `line`, or its replacement.
+    fn found(&mut self, line: &[u8], found: &Match) -> io::Result<()> {
+        match &self.options.replace {
+            Some(template) => {
+                let mut replaced = Vec::new();
+                self.matcher
+                    .expand(line, found, template.as_bytes(), &mut replaced);
+                self.matched(&replaced)
+            }
+            None => self.matched(&line[found.range.clone()]),
+        }
+    }
+
+    fn content(&mut self, line: &[u8], selected: bool) -> io::Result<()> {
+        let replace = selected && self.options.replace.is_some();
+        if !self.options.color && !replace {
+            return self.writer.write_all(line);
+        }
+
+        // @Note: empty matches are only printed when they're replaced.
+        let mut end = 0;
+        for found in self
+            .matcher
+            .find_iter(line)
+            .filter(|found| replace || !found.range.is_empty())
+        {
+            self.writer.write_all(&line[end..found.range.start])?;
+            self.found(line, &found)?;
+            end = found.range.end;
+        }
+        self.writer.write_all(&line[end..])
+    }
+
+    fn text_line(
+        &mut self,
+        number: u64,
+        offset: u64,
+        line: &[u8],
+        selected: bool,
+    ) -> io::Result<()> {
+        let has_context = self.options.before_context > 0 || self.options.after_context > 0;
+        if has_context && self.last_number.is_some_and(|last| number > last + 1) {
+            self.colored(SEPARATOR_COLOR, "--")?;
+            writeln!(self.writer)?;
+        }
+        self.last_number = Some(number);
+
+        self.prefix(number, offset, if selected { ':' } else { '-' })?;
+        self.content(line, selected)?;
+        self.writer.write_all(b"\n")
+    }
+
+    fn only_matching(&mut self, number: u64, offset: u64, line: &[u8]) -> io::Result<()> {
+        for found in self
+            .matcher
+            .find_iter(line)
+            .filter(|found| !found.range.is_empty())
+        {
+            self.prefix(number, offset + found.range.start as u64, ':')?;
+            self.found(line, &found)?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn json_line(
+        &mut self,
+        number: u64,
+        offset: u64,
+        line: &[u8],
+        selected: bool,
+    ) -> io::Result<()> {
+        let submatches = self
+            .matcher
+            .find_iter(line)
+            .filter(|found| !found.range.is_empty())
+            .map(|found| JsonSubmatch {
+                pattern: found.pattern,
+                start: found.range.start,
+                end: found.range.end,
+                text: String::from_utf8_lossy(&line[found.range]),
+            })
+            .collect();
+        let record = JsonLine {
+            kind: if selected { "match" } else { "context" },
+            path: self.path.map(|path| path.to_string_lossy()),
+            line_number: number,
+            offset,
+            line: String::from_utf8_lossy(line),
+            submatches,
+        };
+
+        serde_json::to_writer(&mut self.writer, &record)?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Prints the line numbered `number`, starting at byte `offset`, where `selected`
+    /// tells a selected line from a context one.
+    pub fn line(
+        &mut self,
+        number: u64,
+        offset: u64,
+        line: &[u8],
+        selected: bool,
+    ) -> io::Result<()> {
+        match self.options.mode {
+            Mode::Lines => self.text_line(number, offset, line, selected),
+            Mode::OnlyMatching if selected => self.only_matching(number, offset, line),
+            Mode::Json => self.json_line(number, offset, line, selected),
+            _ => Ok(()),
+        }
+    }
+
+    /// Prints the summary of the file (if any, as per the mode), once it was searched
+    /// and `selected_lines` lines were selected.
+    pub fn finish(&mut self, selected_lines: u64) -> io::Result<()> {
+        match self.options.mode {
+            Mode::Count => {
+                if self.options.with_filename && self.path.is_some() {
+                    self.path()?;
+                    self.colored(SEPARATOR_COLOR, ':')?;
+                }
+                writeln!(self.writer, "{}", selected_lines)
+            }
+            Mode::FilesWithMatches if selected_lines > 0 => {
+                self.path()?;
+                writeln!(self.writer)
+            }
+            Mode::FilesWithoutMatch if selected_lines == 0 => {
+                self.path()?;
+                writeln!(self.writer)
+            }
+            _ => Ok(()),
+        }
+    }
+}
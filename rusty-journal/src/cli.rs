@@ -1,22 +1,273 @@
+use crate::output::OutputFormat;
+use chrono::NaiveDate;
+use rusty_journal::dates;
+use rusty_journal::formats::Format;
+use rusty_journal::recurrence::Recurrence;
+use rusty_journal::storage::Backend;
+use rusty_journal::tasks::{Priority, SortKey};
+use rusty_journal::tracking::Grouping;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 pub enum Action {
     /// Write a new task to the journal file.
+    ///
+    /// Its fields can also be written inline in the text, as in
+    /// `Pay rent +finance !high due:friday every:month` (where flags take precedence).
+    /// Words which aren't valid fields, or which start with `\`, are kept as text.
     Add {
         #[structopt()]
         /// Task text.
         task: String,
+        /// Show how the task would be added, without adding it.
+        #[structopt(short = "n", long)]
+        preview: bool,
+        /// Take the text as is, without looking for inline fields.
+        #[structopt(short, long)]
+        literal: bool,
+        /// Task priority (low, medium or high).
+        #[structopt(short, long)]
+        priority: Option<Priority>,
+        /// Due date, as YYYY-MM-DD, MM-DD, `today`, `tomorrow`, a weekday or e.g. 3d.
+        #[structopt(short, long, parse(try_from_str = dates::parse_due_date))]
+        due: Option<NaiveDate>,
+        /// Tag the task (can be used multiple times).
+        #[structopt(short, long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+        /// Make the task repeat: daily, weekly[:mon,thu], monthly[:31] or every N days (e.g. 3d).
+        #[structopt(short, long)]
+        repeat: Option<Recurrence>,
+        /// Make it a subtask of another task.
+        #[structopt(long)]
+        parent: Option<u64>,
+        /// Task which must be done before this one (can be used multiple times).
+        #[structopt(long, number_of_values = 1)]
+        blocked_by: Vec<u64>,
     },
-    /// Remove an entry from the journal file by its position.
+    /// Mark a task in the journal file as completed (scheduling its next occurrence, if it repeats).
     Done {
         #[structopt()]
-        /// 1-based indexing position.
-        position: usize,
+        /// Task ID, as shown by `list`.
+        id: u64,
+        /// Complete the task even if it has open subtasks or blockers.
+        #[structopt(short, long)]
+        force: bool,
     },
-    /// List all tasks in the journal file.
+    /// Change a task in the journal file.
+    Edit {
+        #[structopt()]
+        /// Task ID, as shown by `list`.
+        id: u64,
+        #[structopt()]
+        /// New task text.
+        task: Option<String>,
+        /// New task priority (low, medium or high).
+        #[structopt(short, long)]
+        priority: Option<Priority>,
+        /// New due date, as YYYY-MM-DD, MM-DD, `today`, `tomorrow`, a weekday or e.g. 3d.
+        #[structopt(short, long, parse(try_from_str = dates::parse_due_date))]
+        due: Option<NaiveDate>,
+        /// Add a tag to the task (can be used multiple times).
+        #[structopt(short, long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+        /// Remove a tag from the task (can be used multiple times).
+        #[structopt(short, long = "untag", number_of_values = 1)]
+        untags: Vec<String>,
+        /// Make it a subtask of another task.
+        #[structopt(long)]
+        parent: Option<u64>,
+        /// Make it a top-level task again.
+        #[structopt(long, conflicts_with = "parent")]
+        no_parent: bool,
+        /// Task which must be done before this one (can be used multiple times).
+        #[structopt(long, number_of_values = 1)]
+        blocked_by: Vec<u64>,
+        /// Task which no longer blocks this one (can be used multiple times).
+        #[structopt(long, number_of_values = 1)]
+        unblocked_by: Vec<u64>,
+    },
+    /// Start tracking the time spent on a task.
+    Start {
+        #[structopt()]
+        /// Task ID, as shown by `list`.
+        id: u64,
+    },
+    /// Stop tracking time on the task being tracked.
+    Stop,
+    /// Show how much time was tracked, grouped by day, week, tag or task.
+    Report {
+        /// How to group the tracked time (day, week, tag or task).
+        #[structopt(short, long, default_value = "day")]
+        by: Grouping,
+        /// Only count time tracked since this date.
+        #[structopt(short, long, parse(try_from_str = dates::parse_due_date))]
+        from: Option<NaiveDate>,
+        /// Only count time tracked until this date.
+        #[structopt(short, long, parse(try_from_str = dates::parse_due_date))]
+        to: Option<NaiveDate>,
+    },
+    /// Revert the last change made to the journal file.
+    Undo,
+    /// List all tasks in the journal file, with subtasks indented under their parents.
+    List {
+        /// Also show completed tasks.
+        #[structopt(short, long)]
+        all: bool,
+        /// Show the archived tasks instead.
+        #[structopt(long)]
+        archived: bool,
+        /// Only show tasks with this tag.
+        #[structopt(short, long)]
+        tag: Option<String>,
+        /// Only show tasks with this priority.
+        #[structopt(short, long)]
+        priority: Option<Priority>,
+        /// Only show open tasks which are past their due date.
+        #[structopt(short, long)]
+        overdue: bool,
+        /// Sort tasks by `id`, `due` date or `priority`.
+        #[structopt(short, long)]
+        sort: Option<SortKey>,
+    },
+    /// Move completed tasks to the archive, hiding them from `list --all`.
+    Archive {
+        #[structopt()]
+        /// IDs of the tasks to archive (every completed task by default).
+        ids: Vec<u64>,
+        /// Only archive tasks completed before this date.
+        #[structopt(short, long, parse(try_from_str = dates::parse_due_date), conflicts_with = "ids")]
+        before: Option<NaiveDate>,
+    },
+    /// Move tasks out of the archive.
+    Unarchive {
+        #[structopt(required = true)]
+        /// IDs of the tasks to unarchive, as shown by `list --archived`.
+        ids: Vec<u64>,
+    },
+    /// Show how many tasks get done per week, how long they take and how many are overdue.
+    Stats {
+        /// Number of weeks to show the throughput of.
+        #[structopt(short, long, default_value = "8")]
+        weeks: usize,
+    },
+    /// Remind of the open tasks which are due soon, once per task (e.g. from a cron job).
+    ///
+    /// Reminders go to a shell command or file (as given here or in the config file),
+    /// or are just printed otherwise.
+    Remind {
+        /// Remind of tasks due up to this many days from today (1 by default).
+        #[structopt(short, long)]
+        within: Option<u32>,
+        /// Shell command run for each task, which gets the task's details in the
+        /// RUSTY_JOURNAL_ID, _TEXT, _DUE, _PRIORITY and _TAGS environment variables
+        /// (and as JSON on its standard input).
+        #[structopt(short, long)]
+        command: Option<String>,
+        /// File to append reminders to.
+        #[structopt(parse(from_os_str), short, long, conflicts_with = "command")]
+        file: Option<PathBuf>,
+    },
+    /// Browse and change the tasks in a full-screen interface.
+    Tui,
+    /// Search for tasks whose text or tags match a query.
+    Search {
+        #[structopt()]
+        /// Text to look for.
+        query: String,
+        /// Ignore case when matching.
+        #[structopt(short, long)]
+        ignore_case: bool,
+        /// Match the query's characters in order, even when not next to each other,
+        /// ranking the results by how well they match.
+        #[structopt(short, long, conflicts_with = "ignore-case")]
+        fuzzy: bool,
+        /// Also search completed tasks.
+        #[structopt(short, long)]
+        all: bool,
+    },
+    /// Write every task in the journal file in the format of another to-do tool
+    /// (which `--format` picks, instead of `--output`).
+    Export {
+        /// Output format (todotxt, csv or markdown).
+        #[structopt(short, long)]
+        format: Format,
+        #[structopt(parse(from_os_str))]
+        /// File to write to (standard output by default).
+        file: Option<PathBuf>,
+    },
+    /// Add the tasks from another to-do tool to the journal file.
+    Import {
+        /// Input format (todotxt, csv or markdown).
+        #[structopt(short, long)]
+        format: Format,
+        #[structopt(parse(from_os_str))]
+        /// File to read from (standard input by default).
+        file: Option<PathBuf>,
+    },
+    /// Manage the named journals in the config file (lists them by default).
+    Journals {
+        #[structopt(subcommand)]
+        action: Option<JournalsAction>,
+    },
+    /// Three-way merge of two journal files against their common base, writing the
+    /// result to OURS (e.g. as a git merge driver: `rusty-journal merge %O %A %B`).
+    Merge {
+        #[structopt(parse(from_os_str))]
+        /// The common ancestor of both journal files.
+        base: PathBuf,
+        #[structopt(parse(from_os_str))]
+        /// Our journal file, which gets replaced with the merged one.
+        ours: PathBuf,
+        #[structopt(parse(from_os_str))]
+        /// Their journal file.
+        theirs: PathBuf,
+    },
+    /// Copy the journal into a new journal file, stored with another backend.
+    Migrate {
+        #[structopt()]
+        /// Backend of the new journal (json, jsonl, sqlite or git).
+        backend: Backend,
+        #[structopt(parse(from_os_str))]
+        /// Path of the new journal file.
+        destination: PathBuf,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum JournalsAction {
+    /// List the named journals.
     List,
+    /// Add a named journal to the config file.
+    Create {
+        #[structopt()]
+        /// Journal name.
+        name: String,
+        /// Journal file (by default, a file named after the journal in the data directory).
+        #[structopt(parse(from_os_str), short, long)]
+        path: Option<PathBuf>,
+        /// Storage backend of the journal file (json, jsonl, sqlite or git).
+        #[structopt(short, long, default_value = "json")]
+        backend: Backend,
+        /// Default sorting of `list` for this journal (id, due or priority).
+        #[structopt(short, long)]
+        sort: Option<SortKey>,
+        /// Make it the default journal.
+        #[structopt(short, long)]
+        default: bool,
+    },
+    /// Remove a named journal from the config file (its journal file is kept).
+    Remove {
+        #[structopt()]
+        /// Journal name.
+        name: String,
+    },
+    /// Make a named journal the default one.
+    Default {
+        #[structopt()]
+        /// Journal name.
+        name: String,
+    },
 }
 
 #[derive(Debug, StructOpt)]
@@ -28,4 +279,20 @@ pub struct CommandLineArgs {
     /// Use a different journal file.
     #[structopt(parse(from_os_str), short, long)]
     pub journal_file: Option<PathBuf>,
+
+    /// Use one of the named journals from the config file.
+    #[structopt(long, conflicts_with = "journal-file")]
+    pub journal: Option<String>,
+
+    /// Storage backend of the journal file (json, jsonl, sqlite or git).
+    #[structopt(short, long)]
+    pub backend: Option<Backend>,
+
+    /// Use a different config file.
+    #[structopt(parse(from_os_str), long)]
+    pub config_file: Option<PathBuf>,
+
+    /// How to print results (table, plain or json) [default: table].
+    #[structopt(short, long)]
+    pub output: Option<OutputFormat>,
 }
@@ -0,0 +1,166 @@
+use crate::storage::{write_atomically, Backend};
+use crate::tasks::SortKey;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Settings of a named journal.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JournalConfig {
+    pub path: PathBuf,
+
+    #[serde(default)]
+    pub backend: Backend,
+
+    /// How `list` sorts this journal's tasks, unless told otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortKey>,
+}
+
+/// Settings of the `remind` subcommand.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RemindConfig {
+    /// Shell command run for each task to be reminded of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// File where reminders are appended, when there's no `command`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+
+    /// How many days ahead of their due date tasks are reminded of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within: Option<u32>,
+}
+
+/// The contents of the configuration file, e.g.:
+///
+/// ```toml
+/// default = "personal"
+///
+/// [journals.personal]
+/// path = "/home/tiago/.rusty-journal.json"
+///
+/// [journals.work]
+/// path = "/home/tiago/work/journal.db"
+/// backend = "sqlite"
+/// sort = "due"
+///
+/// [remind]
+/// command = 'notify-send "Due $RUSTY_JOURNAL_DUE" "$RUSTY_JOURNAL_TEXT"'
+/// within = 2
+/// ```
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Name of the journal used when none is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+
+    #[serde(default)]
+    pub journals: BTreeMap<String, JournalConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remind: Option<RemindConfig>,
+}
+
+/// Returns the path of the configuration file under the user's config directory
+/// (i.e. `$XDG_CONFIG_HOME/rusty-journal/config.toml` on Linux).
+pub fn find_default_config_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|mut path| {
+        path.push("rusty-journal");
+        path.push("config.toml");
+        path
+    })
+}
+
+/// Returns where a new journal called `name` is stored, when no path is given.
+pub fn default_journal_path(name: &str, backend: Backend) -> Option<PathBuf> {
+    let extension = match backend {
+        Backend::Json | Backend::Git => "json",
+        Backend::Jsonl => "jsonl",
+        Backend::Sqlite => "db",
+    };
+
+    dirs::data_dir().map(|mut path| {
+        path.push("rusty-journal");
+        path.push(format!("{}.{}", name, extension));
+        path
+    })
+}
+
+impl Config {
+    /// Read the configuration in `path`, treating a missing file as an empty one.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("invalid config file `{}`", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("could not read config file `{}`", path.display()))
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string_pretty(self)?;
+        write_atomically(path, content.as_bytes())
+            .with_context(|| format!("could not write config file `{}`", path.display()))
+    }
+
+    /// Returns the settings of the journal called `name`, or of the default
+    /// journal if no `name` is given (which may not be configured at all).
+    pub fn journal(&self, name: Option<&str>) -> Result<Option<&JournalConfig>> {
+        match name.or(self.default.as_deref()) {
+            Some(name) => self
+                .journals
+                .get(name)
+                .map(Some)
+                .ok_or_else(|| anyhow!("There's no journal called `{}`", name)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn create_journal(&mut self, name: String, journal: JournalConfig) -> Result<()> {
+        if self.journals.contains_key(&name) {
+            bail!("There's already a journal called `{}`", name);
+        }
+
+        // @Note: the first journal to be created also becomes the default one.
+        if self.journals.is_empty() && self.default.is_none() {
+            self.default = Some(name.clone());
+        }
+        self.journals.insert(name, journal);
+
+        Ok(())
+    }
+
+    /// Forget about the journal called `name` (its file is left untouched).
+    pub fn remove_journal(&mut self, name: &str) -> Result<JournalConfig> {
+        let journal = self
+            .journals
+            .remove(name)
+            .ok_or_else(|| anyhow!("There's no journal called `{}`", name))?;
+
+        if self.default.as_deref() == Some(name) {
+            self.default = None;
+        }
+
+        Ok(journal)
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<()> {
+        if !self.journals.contains_key(name) {
+            bail!("There's no journal called `{}`", name);
+        }
+        self.default = Some(name.to_owned());
+
+        Ok(())
+    }
+}
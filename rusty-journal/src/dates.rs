@@ -0,0 +1,143 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+
+/// Parse a due date given either as `YYYY-MM-DD`, as `MM-DD` (its next occurrence)
+/// or as a day relative to today (e.g. `today`, `tomorrow`, a weekday name such as
+/// `friday`, or a number of days or weeks from now such as `3d` or `2w`).
+pub fn parse_due_date(s: &str) -> Result<NaiveDate, String> {
+    parse_date_relative_to(s, Local::now().date_naive())
+}
+
+/// Same as `parse_due_date`, but with relative dates resolved against `today`.
+pub fn parse_date_relative_to(s: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let s = s.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    // @Note: dates like `10/11` mean October 11th in some places and November 10th
+    // in others, so they're refused instead of guessing.
+    if s.contains('/') {
+        return Err(format!(
+            "ambiguous date `{}` (use YYYY-MM-DD or MM-DD instead)",
+            s
+        ));
+    }
+
+    if let Some(date) = parse_month_day(&s, today) {
+        return Ok(date);
+    }
+    if let Some(date) = parse_offset(&s, today) {
+        return Ok(date);
+    }
+
+    match s.as_str() {
+        "today" => Ok(today),
+        "tomorrow" => Ok(today + Duration::days(1)),
+        _ => match s.parse::<Weekday>() {
+            // @Note: a weekday always refers to its next occurrence, so that
+            // `friday` on a friday means a week from now (and never today).
+            Ok(weekday) => Ok(next_weekday(today, weekday)),
+            Err(_) => Err(format!(
+                "invalid date `{}` (expected YYYY-MM-DD, MM-DD, `today`, `tomorrow`, a weekday or e.g. 3d)",
+                s
+            )),
+        },
+    }
+}
+
+/// Parse `MM-DD` as the first such day from `today` on (e.g. `01-15` in October
+/// means January 15th of next year, and `02-29` the next leap day).
+fn parse_month_day(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (month, day) = s.split_once('-')?;
+    let (month, day): (u32, u32) = (month.parse().ok()?, day.parse().ok()?);
+
+    (today.year()..=today.year() + 8)
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .find(|&date| date >= today)
+}
+
+/// Parse a number of days or weeks from `today`, e.g. `3d` or `2w`.
+fn parse_offset(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (count, days_per_unit) = match s.strip_suffix('d') {
+        Some(count) => (count, 1),
+        None => (s.strip_suffix('w')?, 7),
+    };
+    let count: i64 = count.parse().ok()?;
+
+    today.checked_add_signed(Duration::days(count.checked_mul(days_per_unit)?))
+}
+
+/// Returns the first day after `date` which falls on `weekday`.
+pub fn next_weekday(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_ahead = (7 + weekday.num_days_from_monday() as i64
+        - date.weekday().num_days_from_monday() as i64)
+        % 7;
+    date + Duration::days(if days_ahead == 0 { 7 } else { days_ahead })
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn absolute_dates() {
+        let today = date(2026, 10, 16);
+        assert_eq!(
+            parse_date_relative_to("2026-11-01", today),
+            Ok(date(2026, 11, 1))
+        );
+        assert!(parse_date_relative_to("2026-02-30", today).is_err());
+    }
+
+    #[test]
+    fn relative_dates() {
+        let today = date(2026, 10, 16); // a friday
+        assert_eq!(parse_date_relative_to("today", today), Ok(today));
+        assert_eq!(
+            parse_date_relative_to("Tomorrow", today),
+            Ok(date(2026, 10, 17))
+        );
+        assert_eq!(
+            parse_date_relative_to("monday", today),
+            Ok(date(2026, 10, 19))
+        );
+        assert_eq!(parse_date_relative_to("fri", today), Ok(date(2026, 10, 23)));
+        assert_eq!(parse_date_relative_to("3d", today), Ok(date(2026, 10, 19)));
+        assert_eq!(parse_date_relative_to("2w", today), Ok(date(2026, 10, 30)));
+        assert!(parse_date_relative_to("someday", today).is_err());
+    }
+
+    #[test]
+    fn ambiguous_dates() {
+        let today = date(2026, 10, 16); // a friday
+
+        // A weekday never means today, and a month-day never means the past.
+        assert_eq!(
+            parse_date_relative_to("friday", today),
+            Ok(date(2026, 10, 23))
+        );
+        assert_eq!(parse_date_relative_to("10-16", today), Ok(today));
+        assert_eq!(
+            parse_date_relative_to("01-15", today),
+            Ok(date(2027, 1, 15))
+        );
+        assert_eq!(
+            parse_date_relative_to("02-29", today),
+            Ok(date(2028, 2, 29))
+        );
+        assert!(parse_date_relative_to("02-30", today).is_err());
+
+        // Day and month could be either way around.
+        let error = parse_date_relative_to("10/11", today).unwrap_err();
+        assert!(error.starts_with("ambiguous date"));
+    }
+}
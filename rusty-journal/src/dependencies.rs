@@ -0,0 +1,273 @@
+use crate::tasks::Task;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, ErrorKind};
+
+/// Returns the tasks each task waits on before it can be done, i.e. its subtasks
+/// and the tasks blocking it.
+fn waits_on(tasks: &[Task]) -> BTreeMap<u64, Vec<u64>> {
+    let mut edges: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+    for task in tasks {
+        edges
+            .entry(task.id)
+            .or_default()
+            .extend(task.blocked_by.iter().copied());
+        if let Some(parent) = task.parent {
+            edges.entry(parent).or_default().push(task.id);
+        }
+    }
+    edges
+}
+
+/// Returns a chain of tasks which starts and ends at `id` (each one waiting on
+/// the next), if there's any.
+fn find_cycle(tasks: &[Task], id: u64) -> Option<Vec<u64>> {
+    let edges = waits_on(tasks);
+
+    // @Note: a depth-first search from `id`, where `path` holds the tasks from
+    // `id` up to the one being visited, and each of them is visited only once.
+    let mut path = vec![id];
+    let mut pending = vec![edges.get(&id).map_or(&[][..], |next| &next[..])];
+    let mut visited = BTreeSet::new();
+
+    while let Some(next) = pending.last_mut() {
+        match next.split_first() {
+            Some((&next_id, rest)) => {
+                *next = rest;
+                if next_id == id {
+                    path.push(id);
+                    return Some(path);
+                }
+                if visited.insert(next_id) {
+                    path.push(next_id);
+                    pending.push(edges.get(&next_id).map_or(&[][..], |next| &next[..]));
+                }
+            }
+            None => {
+                pending.pop();
+                path.pop();
+            }
+        }
+    }
+
+    None
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, message)
+}
+
+/// Check that the parent and blockers of the task with the given `id` exist, and
+/// that they don't make it (indirectly) wait on itself.
+pub fn check_links(tasks: &[Task], id: u64) -> io::Result<()> {
+    let task = tasks
+        .iter()
+        .find(|task| task.id == id)
+        .ok_or_else(|| invalid_input(format!("Invalid task id: {}", id)))?;
+
+    for linked in task.parent.iter().chain(&task.blocked_by) {
+        if !tasks.iter().any(|task| task.id == *linked) {
+            return Err(invalid_input(format!(
+                "Invalid task id: {} (no such task in the journal)",
+                linked
+            )));
+        }
+    }
+
+    match find_cycle(tasks, id) {
+        Some(cycle) => {
+            let cycle: Vec<_> = cycle.iter().map(u64::to_string).collect();
+            Err(invalid_input(format!(
+                "Task {} would end up waiting on itself ({})",
+                id,
+                cycle.join(" -> ")
+            )))
+        }
+        None => Ok(()),
+    }
+}
+
+/// What keeps a task from being done.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OpenDependencies {
+    pub subtasks: Vec<u64>,
+    pub blockers: Vec<u64>,
+}
+
+impl OpenDependencies {
+    pub fn of(task: &Task, tasks: &[Task]) -> Self {
+        let is_open = |id: u64| tasks.iter().any(|t| t.id == id && !t.is_completed());
+
+        OpenDependencies {
+            subtasks: tasks
+                .iter()
+                .filter(|t| t.parent == Some(task.id) && !t.is_completed())
+                .map(|t| t.id)
+                .collect(),
+            blockers: task
+                .blocked_by
+                .iter()
+                .copied()
+                .filter(|&id| is_open(id))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subtasks.is_empty() && self.blockers.is_empty()
+    }
+}
+
+impl fmt::Display for OpenDependencies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids = |ids: &[u64]| {
+            let ids: Vec<_> = ids.iter().map(u64::to_string).collect();
+            ids.join(", ")
+        };
+
+        match (self.subtasks.is_empty(), self.blockers.is_empty()) {
+            (false, true) => write!(f, "open subtasks ({})", ids(&self.subtasks)),
+            (true, false) => write!(f, "open blockers ({})", ids(&self.blockers)),
+            _ => write!(
+                f,
+                "open subtasks ({}) and blockers ({})",
+                ids(&self.subtasks),
+                ids(&self.blockers)
+            ),
+        }
+    }
+}
+
+/// A task as shown by `list`, within the tree of subtasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeEntry {
+    #[serde(flatten)]
+    pub task: Task,
+    /// Number of ancestors of the task listed above it.
+    pub depth: usize,
+    /// The tasks blocking this one which are still open.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub open_blockers: Vec<u64>,
+}
+
+/// Arrange the `listed` tasks in a tree, with each one followed by its subtasks
+/// (keeping the order of `listed` among siblings), where `all` are every task in
+/// the journal.
+///
+/// @Note: tasks whose parent isn't listed (e.g. it's completed or was filtered
+/// out) are shown at the top level.
+pub fn tree(listed: Vec<Task>, all: &[Task]) -> Vec<TreeEntry> {
+    let listed_ids: BTreeSet<u64> = listed.iter().map(|task| task.id).collect();
+    let is_root = |task: &Task| {
+        task.parent
+            .is_none_or(|parent| !listed_ids.contains(&parent))
+    };
+
+    let mut entries = Vec::with_capacity(listed.len());
+    let mut visited = BTreeSet::new();
+    let mut pending: Vec<(usize, &Task)> = listed
+        .iter()
+        .filter(|task| is_root(task))
+        .rev()
+        .map(|task| (0, task))
+        .collect();
+
+    while let Some((depth, task)) = pending.pop() {
+        if !visited.insert(task.id) {
+            continue;
+        }
+        entries.push(TreeEntry {
+            task: task.clone(),
+            depth,
+            open_blockers: OpenDependencies::of(task, all).blockers,
+        });
+
+        let children = listed.iter().filter(|child| child.parent == Some(task.id));
+        pending.extend(children.rev().map(|child| (depth + 1, child)));
+    }
+
+    entries
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u64, parent: Option<u64>, blocked_by: &[u64]) -> Task {
+        let mut task = Task::new(format!("task {}", id));
+        task.id = id;
+        task.parent = parent;
+        task.blocked_by = blocked_by.iter().copied().collect();
+        task
+    }
+
+    #[test]
+    fn cycles() {
+        let mut tasks = vec![
+            task(1, None, &[]),
+            task(2, Some(1), &[]),
+            task(3, Some(2), &[4]),
+            task(4, None, &[]),
+        ];
+        for id in 1..=4 {
+            assert!(check_links(&tasks, id).is_ok());
+        }
+
+        // A task can't be the parent of its own ancestor...
+        tasks[0].parent = Some(3);
+        assert_eq!(find_cycle(&tasks, 1), Some(vec![1, 2, 3, 1]));
+        tasks[0].parent = None;
+
+        // ...nor be blocked by a task it blocks, or by one of its ancestors.
+        tasks[3].blocked_by.insert(3);
+        assert_eq!(find_cycle(&tasks, 4), Some(vec![4, 3, 4]));
+        tasks[3].blocked_by.clear();
+        tasks[2].blocked_by.insert(1);
+        assert!(check_links(&tasks, 3).is_err());
+        tasks[2].blocked_by.remove(&1);
+
+        tasks[3].blocked_by.insert(5);
+        assert!(check_links(&tasks, 4).is_err());
+    }
+
+    #[test]
+    fn open_dependencies_and_tree() {
+        let mut tasks = vec![
+            task(1, None, &[]),
+            task(2, None, &[4]),
+            task(3, Some(1), &[]),
+            task(4, None, &[]),
+            task(5, Some(3), &[1]),
+            task(6, Some(1), &[]),
+        ];
+        tasks[5].completed_at = Some(tasks[5].created_at);
+
+        assert_eq!(
+            OpenDependencies::of(&tasks[0], &tasks),
+            OpenDependencies {
+                subtasks: vec![3],
+                blockers: vec![],
+            }
+        );
+        assert_eq!(OpenDependencies::of(&tasks[1], &tasks).blockers, vec![4]);
+
+        let order: Vec<_> = tree(tasks.clone(), &tasks)
+            .into_iter()
+            .map(|entry| (entry.task.id, entry.depth))
+            .collect();
+        assert_eq!(order, vec![(1, 0), (3, 1), (5, 2), (6, 1), (2, 0), (4, 0)]);
+
+        // Subtasks of a task that isn't listed move up to the top level.
+        let listed = vec![tasks[1].clone(), tasks[4].clone()];
+        let order: Vec<_> = tree(listed, &tasks)
+            .into_iter()
+            .map(|entry| (entry.task.id, entry.depth))
+            .collect();
+        assert_eq!(order, vec![(2, 0), (5, 0)]);
+    }
+}
@@ -0,0 +1,636 @@
+use crate::tasks::{Priority, Task};
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, ErrorKind, Read, Write};
+use std::str::{self, FromStr};
+
+/// Formats of other to-do tools that tasks can be exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// http://todotxt.org/ (one task per line).
+    TodoTxt,
+    /// Comma-separated values, with a header row.
+    Csv,
+    /// A Markdown checklist (`- [ ] task`).
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "todotxt" | "todo.txt" => Ok(Format::TodoTxt),
+            "csv" => Ok(Format::Csv),
+            "markdown" | "md" => Ok(Format::Markdown),
+            _ => Err(format!(
+                "invalid format `{}` (expected todotxt, csv or markdown)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::TodoTxt => "todotxt",
+            Format::Csv => "csv",
+            Format::Markdown => "markdown",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Write `tasks` to `writer` in the given `format`.
+pub fn export(tasks: &[Task], format: Format, writer: impl Write) -> io::Result<()> {
+    match format {
+        Format::TodoTxt => export_todotxt(tasks, writer),
+        Format::Csv => export_csv(tasks, writer),
+        Format::Markdown => export_markdown(tasks, writer),
+    }
+}
+
+/// Read tasks in the given `format` from `reader`.
+///
+/// @Note: the imported tasks don't have an ID yet, as they're only given one
+/// when they get added to a journal.
+pub fn import(format: Format, reader: impl Read) -> io::Result<Vec<Task>> {
+    match format {
+        Format::TodoTxt => import_todotxt(io::BufReader::new(reader)),
+        Format::Csv => import_csv(reader),
+        Format::Markdown => import_markdown(io::BufReader::new(reader)),
+    }
+}
+
+fn invalid_data(line_number: usize, message: impl fmt::Display) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("invalid task on line #{}: {}", line_number, message),
+    )
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|e| format!("invalid timestamp `{}` ({})", s, e))
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| format!("invalid date `{}` ({})", s, e))
+}
+
+// @Note: tags are written with `%`, `,` and whitespace percent-encoded (e.g. `two
+// words` as `two%20words`), so that lists of them can be separated by either.
+
+fn escape_tag(tag: &str) -> String {
+    let mut escaped = String::with_capacity(tag.len());
+    for c in tag.chars() {
+        if c == '%' || c == ',' || c.is_whitespace() {
+            for byte in c.to_string().bytes() {
+                escaped.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Decodes the percent-encoded bytes of `tag` (a `%` which doesn't start one is
+/// kept as is).
+fn unescape_tag(tag: &str) -> String {
+    let bytes = tag.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok());
+        match byte {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 3;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// Returns the start of the (local) day `date`, for formats which only keep dates.
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    Local.from_local_datetime(&midnight).earliest().map_or_else(
+        || Utc.from_utc_datetime(&midnight),
+        |t| t.with_timezone(&Utc),
+    )
+}
+
+//
+// todo.txt
+//
+
+// @Note: todo.txt only keeps dates (without a time of day) and drops the priority
+// of completed tasks, so the exact timestamps and the priority are also written
+// as `key:value` extensions, which other todo.txt tools simply show as text.
+//
+// Words of the text which would be read back as a tag or as one of these
+// extensions are escaped with a leading `\` (as are words which already start
+// with one), e.g. `Sell +1 shares` is written as `Sell \+1 shares`. So are empty
+// words at either end of the text, which would otherwise be read back as spaces
+// between fields, e.g. ` Water` is written as `\ Water`.
+
+/// Extensions which are read as fields of the task.
+const TODOTXT_KEYS: [&str; 5] = ["due:", "rec:", "pri:", "created:", "completed:"];
+
+/// Whether `word`, from the text of a task, has to be escaped to be read back as is.
+fn needs_escape(word: &str) -> bool {
+    (word.len() > 1 && word.starts_with('+'))
+        || word.starts_with('\\')
+        || TODOTXT_KEYS.iter().any(|key| word.starts_with(key))
+}
+
+fn priority_letter(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+fn letter_priority(letter: &str) -> Option<Priority> {
+    match letter {
+        "A" => Some(Priority::High),
+        "B" => Some(Priority::Medium),
+        // @Note: todo.txt has 26 priorities, everything after `B` is "low".
+        s if s.len() == 1 && s.chars().all(|c| c.is_ascii_uppercase()) => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+fn export_todotxt(tasks: &[Task], mut writer: impl Write) -> io::Result<()> {
+    for task in tasks {
+        let mut line = String::new();
+
+        if let Some(completed_at) = task.completed_at {
+            line.push_str(&format!(
+                "x {} ",
+                completed_at.with_timezone(&Local).format("%F")
+            ));
+        } else if let Some(priority) = task.priority {
+            line.push_str(&format!("({}) ", priority_letter(priority)));
+        }
+        line.push_str(&format!(
+            "{} ",
+            task.created_at.with_timezone(&Local).format("%F")
+        ));
+        let word_count = task.text.split(' ').count();
+        let words: Vec<String> = task
+            .text
+            .split(' ')
+            .enumerate()
+            .map(|(index, word)| {
+                let at_either_end = index == 0 || index + 1 == word_count;
+                if needs_escape(word) || (word.is_empty() && at_either_end) {
+                    format!("\\{}", word)
+                } else {
+                    word.to_owned()
+                }
+            })
+            .collect();
+        line.push_str(&words.join(" "));
+
+        for tag in &task.tags {
+            line.push_str(&format!(" +{}", escape_tag(tag)));
+        }
+        if let Some(due) = task.due {
+            line.push_str(&format!(" due:{}", due.format("%F")));
+        }
+        if let Some(recurrence) = &task.recurrence {
+            line.push_str(&format!(" rec:{}", recurrence));
+        }
+        if let (Some(priority), true) = (task.priority, task.is_completed()) {
+            line.push_str(&format!(" pri:{}", priority_letter(priority)));
+        }
+        line.push_str(&format!(" created:{}", format_timestamp(task.created_at)));
+        if let Some(completed_at) = task.completed_at {
+            line.push_str(&format!(" completed:{}", format_timestamp(completed_at)));
+        }
+
+        writeln!(writer, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn parse_todotxt_line(line: &str) -> Result<Task, String> {
+    let mut rest = line;
+    let mut task = Task::new(String::new());
+
+    let mut completion_date = None;
+    if let Some(after) = rest.strip_prefix("x ") {
+        rest = after;
+        task.completed_at = Some(Utc::now());
+        if let Some((date, after)) = split_date(rest) {
+            completion_date = Some(date);
+            rest = after;
+        }
+    }
+
+    if let (Some("("), Some(letter), Some(") ")) = (rest.get(..1), rest.get(1..2), rest.get(2..4)) {
+        task.priority = letter_priority(letter);
+        rest = &rest[4..];
+    }
+
+    let mut creation_date = None;
+    if let Some((date, after)) = split_date(rest) {
+        creation_date = Some(date);
+        rest = after;
+    }
+
+    let mut created_at = None;
+    let mut completed_at = None;
+    // Words of the text, and whether they were escaped.
+    let mut words: Vec<(&str, bool)> = Vec::new();
+
+    for word in rest.split(' ') {
+        if let Some(escaped) = word.strip_prefix('\\') {
+            words.push((escaped, true));
+        } else if let Some(tag) = word.strip_prefix('+').filter(|tag| !tag.is_empty()) {
+            task.tags.insert(unescape_tag(tag));
+        } else if let Some(due) = word.strip_prefix("due:") {
+            task.due = Some(parse_date(due)?);
+        } else if let Some(recurrence) = word.strip_prefix("rec:") {
+            task.recurrence = Some(recurrence.parse()?);
+        } else if let Some(letter) = word.strip_prefix("pri:") {
+            task.priority = letter_priority(letter);
+        } else if let Some(timestamp) = word.strip_prefix("created:") {
+            created_at = Some(parse_timestamp(timestamp)?);
+        } else if let Some(timestamp) = word.strip_prefix("completed:") {
+            completed_at = Some(parse_timestamp(timestamp)?);
+        } else {
+            words.push((word, false));
+        }
+    }
+    // @Note: the empty words left at either end come from the spaces around the
+    // fields (which can be anywhere in the line), not from the text.
+    let is_separator = |&(word, escaped): &(&str, bool)| word.is_empty() && !escaped;
+    let start = words.iter().position(|word| !is_separator(word));
+    let end = words.iter().rposition(|word| !is_separator(word));
+    let text: Vec<&str> = match (start, end) {
+        (Some(start), Some(end)) => words[start..=end].iter().map(|&(word, _)| word).collect(),
+        _ => Vec::new(),
+    };
+    task.text = text.join(" ");
+
+    // Prefer the exact timestamps, falling back to the standard todo.txt dates.
+    if let Some(created_at) = created_at.or_else(|| creation_date.map(start_of_day)) {
+        task.created_at = created_at;
+    }
+    if task.is_completed() {
+        task.completed_at = completed_at
+            .or_else(|| completion_date.map(start_of_day))
+            .or(task.completed_at);
+    }
+
+    Ok(task)
+}
+
+/// Splits a leading `YYYY-MM-DD ` date off of `s`.
+fn split_date(s: &str) -> Option<(NaiveDate, &str)> {
+    let (date, rest) = s.split_at(s.find(' ').unwrap_or(s.len()));
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some((date, rest.strip_prefix(' ').unwrap_or(rest)))
+}
+
+fn import_todotxt(reader: impl BufRead) -> io::Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        tasks.push(parse_todotxt_line(&line).map_err(|e| invalid_data(index + 1, e))?);
+    }
+
+    Ok(tasks)
+}
+
+//
+// CSV
+//
+
+#[derive(Deserialize, Serialize)]
+struct CsvRecord {
+    text: String,
+    created_at: String,
+    #[serde(default)]
+    completed_at: String,
+    #[serde(default)]
+    priority: String,
+    #[serde(default)]
+    due: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    recurrence: String,
+}
+
+fn export_csv(tasks: &[Task], writer: impl Write) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for task in tasks {
+        writer.serialize(CsvRecord {
+            text: task.text.clone(),
+            created_at: format_timestamp(task.created_at),
+            completed_at: task.completed_at.map(format_timestamp).unwrap_or_default(),
+            priority: task.priority.map(|p| p.to_string()).unwrap_or_default(),
+            due: task
+                .due
+                .map(|due| due.format("%F").to_string())
+                .unwrap_or_default(),
+            tags: task
+                .tags
+                .iter()
+                .map(|tag| escape_tag(tag))
+                .collect::<Vec<_>>()
+                .join(" "),
+            recurrence: task
+                .recurrence
+                .as_ref()
+                .map(|r| r.to_string())
+                .unwrap_or_default(),
+        })?;
+    }
+    writer.flush()
+}
+
+fn parse_csv_record(record: CsvRecord) -> Result<Task, String> {
+    let mut task = Task::new(record.text);
+    task.created_at = parse_timestamp(&record.created_at)?;
+    if !record.completed_at.is_empty() {
+        task.completed_at = Some(parse_timestamp(&record.completed_at)?);
+    }
+    if !record.priority.is_empty() {
+        task.priority = Some(record.priority.parse()?);
+    }
+    if !record.due.is_empty() {
+        task.due = Some(parse_date(&record.due)?);
+    }
+    task.tags = record.tags.split_whitespace().map(unescape_tag).collect();
+    if !record.recurrence.is_empty() {
+        task.recurrence = Some(record.recurrence.parse()?);
+    }
+
+    Ok(task)
+}
+
+fn import_csv(reader: impl Read) -> io::Result<Vec<Task>> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let mut tasks = Vec::new();
+    for (index, record) in reader.deserialize().enumerate() {
+        // @Note: the header takes the first line, so records start at line #2.
+        tasks.push(parse_csv_record(record?).map_err(|e| invalid_data(index + 2, e))?);
+    }
+
+    Ok(tasks)
+}
+
+//
+// Markdown
+//
+
+// @Note: besides the checkbox and the text, every field is kept inside an HTML
+// comment at the end of the item, so that it doesn't show up when rendered.
+
+const COMMENT_START: &str = " <!-- ";
+const COMMENT_END: &str = " -->";
+
+fn export_markdown(tasks: &[Task], mut writer: impl Write) -> io::Result<()> {
+    for task in tasks {
+        let mut fields = vec![format!("created_at={}", format_timestamp(task.created_at))];
+        if let Some(completed_at) = task.completed_at {
+            fields.push(format!("completed_at={}", format_timestamp(completed_at)));
+        }
+        if let Some(priority) = task.priority {
+            fields.push(format!("priority={}", priority));
+        }
+        if let Some(due) = task.due {
+            fields.push(format!("due={}", due.format("%F")));
+        }
+        if !task.tags.is_empty() {
+            fields.push(format!(
+                "tags={}",
+                task.tags
+                    .iter()
+                    .map(|tag| escape_tag(tag))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ));
+        }
+
+        if let Some(recurrence) = &task.recurrence {
+            fields.push(format!("recurrence={}", recurrence));
+        }
+
+        let checkbox = if task.is_completed() { 'x' } else { ' ' };
+        writeln!(
+            writer,
+            "- [{}] {}{}{}{}",
+            checkbox,
+            task.text,
+            COMMENT_START,
+            fields.join(" "),
+            COMMENT_END
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns `None` for lines which aren't checklist items (e.g. headings).
+fn parse_markdown_line(line: &str) -> Option<Result<Task, String>> {
+    let line = line.trim_start();
+    let item = line
+        .strip_prefix("- [")
+        .or_else(|| line.strip_prefix("* ["))?;
+
+    let mut task = Task::new(String::new());
+    let rest = match item.get(..2) {
+        Some(" ]") => &item[2..],
+        Some("x]") | Some("X]") => {
+            task.completed_at = Some(Utc::now());
+            &item[2..]
+        }
+        _ => return None,
+    };
+    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+
+    let (text, fields) = match rest.strip_suffix(COMMENT_END) {
+        Some(rest) => match rest.rfind(COMMENT_START) {
+            Some(start) => (&rest[..start], &rest[start + COMMENT_START.len()..]),
+            None => (rest, ""),
+        },
+        None => (rest, ""),
+    };
+    task.text = text.to_owned();
+
+    let mut parse_fields = || -> Result<(), String> {
+        for field in fields.split_whitespace() {
+            match field.split_once('=') {
+                Some(("created_at", value)) => task.created_at = parse_timestamp(value)?,
+                Some(("completed_at", value)) => task.completed_at = Some(parse_timestamp(value)?),
+                Some(("priority", value)) => task.priority = Some(value.parse()?),
+                Some(("due", value)) => task.due = Some(parse_date(value)?),
+                Some(("tags", value)) => task.tags = value.split(',').map(unescape_tag).collect(),
+                Some(("recurrence", value)) => task.recurrence = Some(value.parse()?),
+                _ => {} // ignore anything we don't know about
+            }
+        }
+        Ok(())
+    };
+
+    Some(parse_fields().map(|_| task))
+}
+
+fn import_markdown(reader: impl BufRead) -> io::Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        if let Some(task) = parse_markdown_line(&line?) {
+            tasks.push(task.map_err(|e| invalid_data(index + 1, e))?);
+        }
+    }
+
+    Ok(tasks)
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recurrence::Recurrence;
+
+    fn sample_tasks() -> Vec<Task> {
+        let mut plain = Task::new("Water  the plants".to_owned());
+        plain.created_at = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+
+        let mut full = Task::new("Pay rent, finally (\"urgent\")".to_owned());
+        full.created_at = Utc.timestamp_opt(1_700_000_123, 0).unwrap();
+        full.priority = Some(Priority::High);
+        full.due = NaiveDate::from_ymd_opt(2026, 11, 1);
+        full.recurrence = Some(Recurrence::Monthly { day: Some(31) });
+        full.tags = vec!["finance".to_owned(), "home".to_owned()]
+            .into_iter()
+            .collect();
+
+        let mut completed = Task::new("Call mom".to_owned());
+        completed.created_at = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        completed.completed_at = Some(Utc.timestamp_opt(1_700_086_399, 0).unwrap());
+        completed.priority = Some(Priority::Low);
+
+        vec![plain, full, completed]
+    }
+
+    fn round_trip(format: Format) -> Vec<Task> {
+        let mut exported = Vec::new();
+        export(&sample_tasks(), format, &mut exported).unwrap();
+        import(format, exported.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn todotxt_is_lossless() {
+        assert_eq!(round_trip(Format::TodoTxt), sample_tasks());
+    }
+
+    #[test]
+    fn todotxt_escapes_special_words() {
+        let mut task =
+            Task::new("Sell +1 shares due:friday (see C:\\notes) created:today +".to_owned());
+        task.created_at = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        task.tags.insert("finance".to_owned());
+        let tasks = vec![task];
+
+        let mut exported = Vec::new();
+        export(&tasks, Format::TodoTxt, &mut exported).unwrap();
+        let exported = String::from_utf8(exported).unwrap();
+        assert!(exported.contains(
+            " Sell \\+1 shares \\due:friday (see C:\\notes) \\created:today + +finance "
+        ));
+        assert_eq!(import(Format::TodoTxt, exported.as_bytes()).unwrap(), tasks);
+
+        let tasks = import(Format::TodoTxt, r"Read \\server\docs".as_bytes()).unwrap();
+        assert_eq!(tasks[0].text, r"Read \server\docs");
+    }
+
+    #[test]
+    fn csv_is_lossless() {
+        assert_eq!(round_trip(Format::Csv), sample_tasks());
+    }
+
+    #[test]
+    fn markdown_is_lossless() {
+        assert_eq!(round_trip(Format::Markdown), sample_tasks());
+    }
+
+    #[test]
+    fn special_tags_and_whitespace() {
+        let mut task = Task::new("  Water\tthe plants ".to_owned());
+        task.created_at = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        task.tags = vec!["two words", "a,b", "50%", "c:\\"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        let tasks = vec![task];
+
+        for format in [Format::TodoTxt, Format::Csv, Format::Markdown] {
+            let mut exported = Vec::new();
+            export(&tasks, format, &mut exported).unwrap();
+            let imported = import(format, exported.as_slice()).unwrap();
+            assert_eq!(imported, tasks, "{}", format);
+        }
+    }
+
+    #[test]
+    fn todotxt_from_other_tools() {
+        let tasks = import(
+            Format::TodoTxt,
+            "(A) 2026-10-01 Call the bank +finance @phone\nx 2026-10-02 2026-10-01 Buy milk\n"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].text, "Call the bank @phone");
+        assert_eq!(tasks[0].priority, Some(Priority::High));
+        assert!(tasks[0].tags.contains("finance"));
+        assert_eq!(tasks[1].text, "Buy milk");
+        assert!(tasks[1].is_completed());
+    }
+
+    #[test]
+    fn markdown_from_other_tools() {
+        let tasks = import(
+            Format::Markdown,
+            "# Groceries\n\n- [ ] Eggs\n- [x] Flour\nSome notes\n".as_bytes(),
+        )
+        .unwrap();
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].text, "Eggs");
+        assert!(!tasks[0].is_completed());
+        assert_eq!(tasks[1].text, "Flour");
+        assert!(tasks[1].is_completed());
+    }
+}
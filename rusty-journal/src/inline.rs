@@ -0,0 +1,161 @@
+use crate::dates;
+use crate::recurrence::Recurrence;
+use crate::tasks::Priority;
+use chrono::{Local, NaiveDate};
+use std::collections::BTreeSet;
+
+/// The fields of a task, as written inline in its text, e.g.:
+///
+/// `Pay rent +finance !high due:friday every:month`
+///
+/// where `+tag` adds a tag (when it starts with a letter), `!priority` sets the
+/// priority, `due:date` the due date (as accepted by `dates::parse_due_date`) and
+/// `every:recurrence` makes the task repeat (as accepted by `Recurrence`'s
+/// `FromStr`, or a weekday, e.g. `every:mon`).
+///
+/// Words which aren't valid fields are kept as text, as are words starting with
+/// `\` (without it, e.g. `\!high`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InlineTask {
+    pub text: String,
+    pub priority: Option<Priority>,
+    pub due: Option<NaiveDate>,
+    pub tags: BTreeSet<String>,
+    pub recurrence: Option<Recurrence>,
+}
+
+fn parse_recurrence(s: &str) -> Result<Recurrence, String> {
+    s.parse()
+        .or_else(|e| format!("weekly:{}", s).parse().map_err(|_| e))
+}
+
+/// Reads `word` into the field of `task` it stands for, returning whether it did.
+fn parse_field(task: &mut InlineTask, word: &str, today: NaiveDate) -> bool {
+    if let Some(tag) = word
+        .strip_prefix('+')
+        .filter(|tag| tag.starts_with(char::is_alphabetic))
+    {
+        task.tags.insert(tag.to_owned());
+    } else if let Some(priority) = word.strip_prefix('!').and_then(|p| p.parse().ok()) {
+        task.priority = Some(priority);
+    } else if let Some(due) = word
+        .strip_prefix("due:")
+        .and_then(|due| dates::parse_date_relative_to(due, today).ok())
+    {
+        task.due = Some(due);
+    } else if let Some(recurrence) = word
+        .strip_prefix("every:")
+        .and_then(|recurrence| parse_recurrence(recurrence).ok())
+    {
+        task.recurrence = Some(recurrence);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// Same as `parse`, with relative dates resolved against `today`.
+pub fn parse_relative_to(input: &str, today: NaiveDate) -> Result<InlineTask, String> {
+    let mut task = InlineTask::default();
+
+    // @Note: the text is kept as it was written (spacing included), with the
+    // fields cut out of it along with the whitespace before them.
+    let leading = &input[..input.len() - input.trim_start().len()];
+    let trailing = &input[input.trim_end().len()..];
+    let mut text = String::with_capacity(input.len());
+    let mut rest = input.trim();
+    let mut whitespace = leading;
+    while !rest.is_empty() {
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(word_end);
+        let next_word = after.trim_start();
+
+        if !parse_field(&mut task, word, today) {
+            text.push_str(if text.is_empty() { leading } else { whitespace });
+            text.push_str(word.strip_prefix('\\').unwrap_or(word));
+        }
+        whitespace = &after[..after.len() - next_word.len()];
+        rest = next_word;
+    }
+
+    if text.trim().is_empty() {
+        return Err(format!("`{}` has no text besides its fields", input));
+    }
+    text.push_str(trailing);
+    task.text = text;
+
+    Ok(task)
+}
+
+/// Parse the task fields written inline in `input`, returning the remaining text.
+pub fn parse(input: &str) -> Result<InlineTask, String> {
+    parse_relative_to(input, Local::now().date_naive())
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn inline_fields() {
+        let today = date(2026, 10, 16); // a friday
+        let task =
+            parse_relative_to("Pay rent +finance !high due:friday every:month", today).unwrap();
+        assert_eq!(
+            task,
+            InlineTask {
+                text: "Pay rent".to_owned(),
+                priority: Some(Priority::High),
+                due: Some(date(2026, 10, 23)),
+                tags: vec!["finance".to_owned()].into_iter().collect(),
+                recurrence: Some(Recurrence::Monthly { day: None }),
+            }
+        );
+
+        let task = parse_relative_to("Gym every:mon,thu", today).unwrap();
+        assert_eq!(task.recurrence, "weekly:mon,thu".parse().ok());
+
+        // Escaped and unrecognized words stay in the text.
+        let task = parse_relative_to("Upvote \\+finance and \\due:now, wow!! + !!", today).unwrap();
+        assert_eq!(task.text, "Upvote +finance and due:now, wow!! + !!");
+        assert_eq!(
+            task,
+            InlineTask {
+                text: task.text.clone(),
+                ..Default::default()
+            }
+        );
+
+        assert!(parse_relative_to("+finance !high", today).is_err());
+    }
+
+    #[test]
+    fn text_is_kept_as_is() {
+        let today = date(2026, 10, 16);
+        let text_of = |input| parse_relative_to(input, today).unwrap().text;
+
+        // Spacing is kept, besides the one before the fields.
+        assert_eq!(text_of("Water\tthe  plants +home"), "Water\tthe  plants");
+        assert_eq!(text_of(" +home  Water the plants "), " Water the plants ");
+        assert_eq!(text_of("Water +home\tthe plants"), "Water\tthe plants");
+
+        // Words which only look like tags aren't ones.
+        let task = parse_relative_to("Upvote +1 (+ +-)", today).unwrap();
+        assert_eq!(task.text, "Upvote +1 (+ +-)");
+        assert!(task.tags.is_empty());
+
+        // Nor are invalid dates or recurrences, which aren't errors either.
+        let task = parse_relative_to("check due:diligence notes every:fortnight", today).unwrap();
+        assert_eq!(task.text, "check due:diligence notes every:fortnight");
+        assert_eq!((task.due, task.recurrence), (None, None));
+        assert_eq!(text_of("Pay rent due:10/11"), "Pay rent due:10/11");
+    }
+}
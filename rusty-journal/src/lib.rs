@@ -0,0 +1,31 @@
+//! A to-do journal, which can be embedded in other tools.
+//!
+//! Every operation on a journal lives in [`tasks`], and returns data instead of
+//! printing it, e.g.:
+//!
+//! ```no_run
+//! use rusty_journal::storage::{Backend, Journal};
+//! use rusty_journal::tasks::{self, ListOptions, Task};
+//!
+//! let journal = Journal::new("journal.json".into(), Backend::Json);
+//! tasks::add_task(&journal, Task::new("Write the docs".to_owned()))?;
+//!
+//! for task in tasks::list_tasks(&journal, &ListOptions::default())? {
+//!     println!("{}: {}", task.id, task.text);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod config;
+pub mod dates;
+pub mod dependencies;
+pub mod formats;
+pub mod inline;
+pub mod merge;
+pub mod recurrence;
+pub mod reminders;
+pub mod search;
+pub mod stats;
+pub mod storage;
+pub mod tasks;
+pub mod tracking;
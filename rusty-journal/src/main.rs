@@ -1,8 +1,16 @@
 mod cli;
-mod tasks;
+mod output;
+mod tui;
 
-use anyhow::anyhow;
-use std::path::PathBuf;
+use anyhow::{anyhow, bail, Context};
+use output::OutputFormat;
+use rusty_journal::config::{self, Config, JournalConfig};
+use rusty_journal::inline::{self, InlineTask};
+use rusty_journal::reminders::{self, Hook};
+use rusty_journal::{formats, search, stats, storage, tasks, tracking};
+use std::fs::{self, File};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 fn find_default_journal_file() -> Option<PathBuf> {
@@ -12,24 +20,384 @@ fn find_default_journal_file() -> Option<PathBuf> {
     })
 }
 
+fn manage_journals(
+    action: cli::JournalsAction,
+    mut config: Config,
+    config_file: &Path,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    use cli::JournalsAction::*;
+
+    match action {
+        List => {
+            let journals = config
+                .journals
+                .iter()
+                .map(|(name, journal)| output::NamedJournal {
+                    name: name.clone(),
+                    path: journal.path.clone(),
+                    backend: journal.backend,
+                    default: config.default.as_ref() == Some(name),
+                })
+                .collect();
+            return output::print(&output::JournalList(journals), format);
+        }
+        Create {
+            name,
+            path,
+            backend,
+            sort,
+            default,
+        } => {
+            let path = path
+                .or_else(|| config::default_journal_path(&name, backend))
+                .ok_or_else(|| anyhow!("Failed to find a path for the journal file"))?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            config.create_journal(
+                name.clone(),
+                JournalConfig {
+                    path,
+                    backend,
+                    sort,
+                },
+            )?;
+            if default {
+                config.set_default(&name)?;
+            }
+        }
+        Remove { name } => {
+            let journal = config.remove_journal(&name)?;
+            let message = format!(
+                "Removed journal `{}` (its file was kept at {})",
+                name,
+                journal.path.display()
+            );
+            config.save(config_file)?;
+            return output::print(&output::Message { message }, format);
+        }
+        Default { name } => config.set_default(&name)?,
+    }
+
+    config.save(config_file)
+}
+
+/// Prints the result of a subcommand which changed a single task.
+fn print_task(verb: &'static str, task: tasks::Task, format: OutputFormat) -> anyhow::Result<()> {
+    output::print(&output::TaskChange { verb, task }, format)
+}
+
 fn main() -> anyhow::Result<()> {
     use cli::{Action::*, CommandLineArgs};
-    use tasks::Task;
+    use storage::Journal;
+    use tasks::{ListOptions, Task};
 
     let CommandLineArgs {
         action,
         journal_file,
+        journal,
+        backend,
+        config_file,
+        output,
     } = CommandLineArgs::from_args();
+    let format = output.unwrap_or(OutputFormat::Table);
+
+    let config_file = config_file
+        .or_else(config::find_default_config_file)
+        .ok_or_else(|| anyhow!("Failed to find config file"))?;
+    let config = Config::load(&config_file)?;
 
-    let journal_file = journal_file
-        .or_else(find_default_journal_file)
-        .ok_or_else(|| anyhow!("Failed to find journal file"))?;
+    let action = match action {
+        Journals { action } => {
+            return manage_journals(
+                action.unwrap_or(cli::JournalsAction::List),
+                config,
+                &config_file,
+                format,
+            );
+        }
+        action => action,
+    };
+
+    // @Note: an explicit `--journal-file` takes precedence over the named journals
+    // in the config file, which in turn take precedence over the default file.
+    let journal_config = match journal_file {
+        Some(_) => None,
+        None => config.journal(journal.as_deref())?,
+    };
+    let journal = match (journal_file, journal_config) {
+        (Some(journal_file), _) => Journal::new(journal_file, backend.unwrap_or_default()),
+        (None, Some(journal_config)) => Journal::new(
+            journal_config.path.clone(),
+            backend.unwrap_or(journal_config.backend),
+        ),
+        (None, None) => {
+            let journal_file = find_default_journal_file()
+                .ok_or_else(|| anyhow!("Failed to find journal file"))?;
+            Journal::new(journal_file, backend.unwrap_or_default())
+        }
+    };
 
     match action {
-        Add { task: text } => tasks::add_task(journal_file, Task::new(text)),
-        Done { position } => tasks::complete_task(journal_file, position),
-        List => tasks::list_tasks(journal_file),
-    }?;
+        Add {
+            task: text,
+            preview,
+            literal,
+            priority,
+            due,
+            tags,
+            repeat,
+            parent,
+            blocked_by,
+        } => {
+            let inline = if literal {
+                InlineTask {
+                    text,
+                    ..InlineTask::default()
+                }
+            } else {
+                inline::parse(&text).map_err(|e| anyhow!(e))?
+            };
+
+            let mut task = Task::new(inline.text);
+            task.priority = priority.or(inline.priority);
+            task.due = due.or(inline.due);
+            task.tags = inline.tags;
+            task.tags.extend(tags);
+            task.parent = parent;
+            task.blocked_by = blocked_by.into_iter().collect();
+            if let Some(recurrence) = repeat.or(inline.recurrence) {
+                task.set_recurrence(recurrence);
+            }
+
+            if preview {
+                output::print(&output::Preview(task), format)
+            } else {
+                print_task("Added", tasks::add_task(&journal, task)?, format)
+            }
+        }
+        Done { id, force } => {
+            let completion = tasks::complete_task(&journal, id, force)?;
+            if !completion.open_dependencies.is_empty() {
+                eprintln!(
+                    "Warning: task {} was completed with {}",
+                    id, completion.open_dependencies
+                );
+            }
+            output::print(&completion, format)
+        }
+        Edit {
+            id,
+            task: text,
+            priority,
+            due,
+            tags,
+            untags,
+            parent,
+            no_parent,
+            blocked_by,
+            unblocked_by,
+        } => {
+            let task = tasks::edit_task(&journal, id, |task| {
+                if let Some(text) = text {
+                    task.text = text;
+                }
+                task.priority = priority.or(task.priority);
+                task.due = due.or(task.due);
+                task.tags.extend(tags);
+                for tag in untags {
+                    task.tags.remove(&tag);
+                }
+                if no_parent {
+                    task.parent = None;
+                }
+                task.parent = parent.or(task.parent);
+                task.blocked_by.extend(blocked_by);
+                for blocker in unblocked_by {
+                    task.blocked_by.remove(&blocker);
+                }
+            })?;
+            print_task("Edited", task, format)
+        }
+        Start { id } => print_task("Started", tasks::start_task(&journal, id)?, format),
+        Stop => output::print(&output::Stopped(tasks::stop_task(&journal)?), format),
+        Report { by, from, to } => {
+            let rows = tracking::report(&tasks::load_tasks(&journal)?, by, from, to);
+            output::print(&output::TimeReport(rows), format)
+        }
+        Undo => {
+            tasks::undo(&journal)?;
+            let message = "Reverted the last change".to_owned();
+            output::print(&output::Message { message }, format)
+        }
+        List {
+            all,
+            archived,
+            tag,
+            priority,
+            overdue,
+            sort,
+        } => {
+            let options = ListOptions {
+                show_completed: all,
+                archived,
+                tag,
+                priority,
+                overdue,
+                sort: sort.or_else(|| journal_config.and_then(|journal| journal.sort)),
+            };
+            let tree = tasks::list_tree(&journal, &options)?;
+            output::print(&output::TaskTree(tree), format)
+        }
+        Archive { ids, before } => {
+            let archived = tasks::archive_tasks(&journal, &ids, before)?;
+            let count = archived.len();
+            output::print(
+                &output::TaskCount {
+                    verb: "Archived",
+                    count,
+                },
+                format,
+            )
+        }
+        Unarchive { ids } => {
+            let count = tasks::unarchive_tasks(&journal, &ids)?;
+            output::print(
+                &output::TaskCount {
+                    verb: "Unarchived",
+                    count,
+                },
+                format,
+            )
+        }
+        Stats { weeks } => {
+            let stats = stats::stats(&tasks::load_tasks(&journal)?, weeks);
+            output::print(&output::StatsReport(stats), format)
+        }
+        Remind {
+            within,
+            command,
+            file,
+        } => {
+            let remind_config = config.remind.unwrap_or_default();
+            let hook = match (command, file) {
+                (Some(command), _) => Hook::Command(command),
+                (None, Some(file)) => Hook::File(file),
+                (None, None) => match (remind_config.command, remind_config.file) {
+                    (Some(command), _) => Hook::Command(command),
+                    (None, Some(file)) => Hook::File(file),
+                    (None, None) => Hook::None,
+                },
+            };
+            let within = within.or(remind_config.within).unwrap_or(1);
 
-    Ok(())
+            let today = chrono::Local::now().date_naive();
+            let reminded = reminders::remind(&journal, &hook, today, within)?;
+            if hook == Hook::None {
+                output::print(&output::TaskList(reminded), format)
+            } else {
+                let count = reminded.len();
+                output::print(
+                    &output::TaskCount {
+                        verb: "Reminded of",
+                        count,
+                    },
+                    format,
+                )
+            }
+        }
+        Tui => {
+            let options = ListOptions {
+                sort: journal_config.and_then(|journal| journal.sort),
+                ..ListOptions::default()
+            };
+            Ok(tui::run(journal, options)?)
+        }
+        Search {
+            query,
+            ignore_case,
+            fuzzy,
+            all,
+        } => {
+            let mode = if fuzzy {
+                search::MatchMode::Fuzzy
+            } else if ignore_case {
+                search::MatchMode::IgnoreCase
+            } else {
+                search::MatchMode::Substring
+            };
+            let results = output::SearchResults {
+                results: tasks::search_tasks(&journal, &query, mode, all)?,
+                use_color: io::stdout().is_terminal(),
+            };
+            output::print(&results, format)
+        }
+        Export { format, file } => {
+            if output.is_some() {
+                bail!(
+                    "`export` doesn't take `--output`, but `--format` (todotxt, csv or markdown)"
+                );
+            }
+            let tasks = tasks::load_tasks(&journal)?;
+            match file {
+                Some(path) => {
+                    let file = File::create(&path)
+                        .with_context(|| format!("could not create file `{}`", path.display()))?;
+                    formats::export(&tasks, format, io::BufWriter::new(file))?;
+                }
+                None => formats::export(&tasks, format, io::stdout().lock())?,
+            }
+            Ok(())
+        }
+        Import {
+            format: file_format,
+            file,
+        } => {
+            let imported = match file {
+                Some(path) => {
+                    let file = File::open(&path)
+                        .with_context(|| format!("could not read file `{}`", path.display()))?;
+                    formats::import(file_format, file)?
+                }
+                None => formats::import(file_format, io::stdin().lock())?,
+            };
+            let count = tasks::import_tasks(&journal, imported)?;
+            output::print(
+                &output::TaskCount {
+                    verb: "Imported",
+                    count,
+                },
+                format,
+            )
+        }
+        Journals { .. } => unreachable!(),
+        Merge { base, ours, theirs } => {
+            // @Note: the three files are read with the backend of the current journal.
+            let open = |path| Journal::new(path, journal.backend);
+            let merge = tasks::merge_journals(&open(base), &open(ours), &open(theirs))?;
+            output::print(&output::MergeReport(merge.clone()), format)?;
+            if !merge.conflicts.is_empty() {
+                bail!(
+                    "The merge had {} conflicting changes (our side was kept)",
+                    merge.conflicts.len()
+                );
+            }
+            Ok(())
+        }
+        Migrate {
+            backend,
+            destination,
+        } => {
+            let count = tasks::migrate(&journal, &Journal::new(destination, backend))?;
+            output::print(
+                &output::TaskCount {
+                    verb: "Migrated",
+                    count,
+                },
+                format,
+            )
+        }
+    }
 }
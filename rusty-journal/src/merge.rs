@@ -0,0 +1,267 @@
+use crate::tasks::Task;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+/// A field of a task which was changed differently on both sides of a merge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Conflict {
+    pub id: u64,
+    pub field: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Merge {
+    pub tasks: Vec<Task>,
+    /// Conflicting changes, where our side was kept.
+    pub conflicts: Vec<Conflict>,
+    /// Tasks added on their side which were given a new ID (since we added
+    /// another task with the same ID), as `(old ID, new ID)` pairs.
+    pub renumbered: Vec<(u64, u64)>,
+}
+
+/// Three-way merge of a single value, where `None` means it's missing: a side
+/// which didn't change keeps the other side's change. Returns `Err` with our
+/// side when both sides changed it differently.
+fn merge_value<T: PartialEq + Clone>(
+    base: Option<&T>,
+    ours: Option<&T>,
+    theirs: Option<&T>,
+) -> Result<Option<T>, Option<T>> {
+    if ours == theirs || theirs == base {
+        Ok(ours.cloned())
+    } else if ours == base {
+        Ok(theirs.cloned())
+    } else {
+        Err(ours.cloned())
+    }
+}
+
+/// Merges the fields of a task changed on both sides one by one, so that e.g.
+/// one side completing it while the other one tags it doesn't conflict.
+fn merge_fields(
+    id: u64,
+    base: Option<&Task>,
+    ours: &Task,
+    theirs: &Task,
+    conflicts: &mut Vec<Conflict>,
+) -> io::Result<Task> {
+    let fields = |task: Option<&Task>| -> io::Result<Map<String, Value>> {
+        match task.map(serde_json::to_value).transpose()? {
+            Some(Value::Object(fields)) => Ok(fields),
+            _ => Ok(Map::new()),
+        }
+    };
+    let (base, ours, theirs) = (fields(base)?, fields(Some(ours))?, fields(Some(theirs))?);
+
+    let names: BTreeSet<&String> = base
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .collect();
+    let mut merged = Map::new();
+    for name in names {
+        let value =
+            merge_value(base.get(name), ours.get(name), theirs.get(name)).unwrap_or_else(|ours| {
+                conflicts.push(Conflict {
+                    id,
+                    field: name.clone(),
+                });
+                ours
+            });
+        if let Some(value) = value {
+            merged.insert(name.clone(), value);
+        }
+    }
+
+    Ok(serde_json::from_value(Value::Object(merged))?)
+}
+
+/// Three-way merge of two versions of a journal (`ours` and `theirs`) which
+/// diverged from `base`, matching tasks by ID. Tasks which get a new ID are given
+/// the ones from `next_id` on (see `Contents::next_id`).
+///
+/// @Note: tasks are only removed by undoing their addition, so a task missing on
+/// one side which is in `base` was removed there, and stays removed unless the
+/// other side changed it. One which isn't in `base` was added on the other side.
+pub fn merge(
+    base: Vec<Task>,
+    ours: Vec<Task>,
+    theirs: Vec<Task>,
+    next_id: u64,
+) -> io::Result<Merge> {
+    let by_id = |tasks: Vec<Task>| -> BTreeMap<u64, Task> {
+        tasks.into_iter().map(|task| (task.id, task)).collect()
+    };
+    let (base, ours, mut theirs) = (by_id(base), by_id(ours), by_id(theirs));
+
+    // Tasks added on both sides with the same ID are different tasks, so theirs
+    // get new IDs (never given before, nor in use), and links to them are updated.
+    let mut next_id = base
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .max()
+        .map_or(1, |id| id + 1)
+        .max(next_id);
+    let renumbered: Vec<(u64, u64)> = theirs
+        .keys()
+        .filter(|id| {
+            !base.contains_key(id)
+                && ours
+                    .get(id)
+                    .is_some_and(|task| Some(task) != theirs.get(id))
+        })
+        .map(|&id| {
+            next_id += 1;
+            (id, next_id - 1)
+        })
+        .collect();
+    if !renumbered.is_empty() {
+        let new_ids: BTreeMap<u64, u64> = renumbered.iter().copied().collect();
+        let renumber = |id: u64| new_ids.get(&id).copied().unwrap_or(id);
+
+        theirs = theirs
+            .into_iter()
+            .map(|(id, mut task)| {
+                if !base.contains_key(&id) {
+                    task.id = renumber(id);
+                }
+                task.parent = task.parent.map(renumber);
+                task.blocked_by = task.blocked_by.iter().map(|&id| renumber(id)).collect();
+                (task.id, task)
+            })
+            .collect();
+    }
+
+    let ids: BTreeSet<u64> = base
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .copied()
+        .collect();
+    let mut tasks = Vec::with_capacity(ids.len());
+    let mut conflicts = Vec::new();
+    for id in ids {
+        let (base, ours, theirs) = (base.get(&id), ours.get(&id), theirs.get(&id));
+        let merged = match merge_value(base, ours, theirs) {
+            Ok(merged) => merged,
+            Err(_) => match (ours, theirs) {
+                (Some(ours), Some(theirs)) => {
+                    Some(merge_fields(id, base, ours, theirs, &mut conflicts)?)
+                }
+                // @Note: one side removed a task the other one changed, so the
+                // changed task is kept.
+                (ours, theirs) => {
+                    conflicts.push(Conflict {
+                        id,
+                        field: "(removed)".to_owned(),
+                    });
+                    ours.or(theirs).cloned()
+                }
+            },
+        };
+        tasks.extend(merged);
+    }
+
+    Ok(Merge {
+        tasks,
+        conflicts,
+        renumbered,
+    })
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn task(id: u64, text: &str) -> Task {
+        let mut task = Task::new(text.to_owned());
+        task.id = id;
+        task.created_at = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        task
+    }
+
+    #[test]
+    fn changes_to_different_tasks_and_fields() {
+        let base = vec![task(1, "Water the plants"), task(2, "Pay rent")];
+
+        let mut ours = base.clone();
+        ours[0].text = "Water the cactus".to_owned();
+        ours[1].tags.insert("home".to_owned());
+        ours.push(task(3, "Call mom"));
+
+        let mut theirs = base.clone();
+        theirs[1].completed_at = Some(Utc::now());
+        theirs.push(task(3, "Buy milk"));
+        let mut subtask = task(4, "Buy bread");
+        subtask.parent = Some(3);
+        theirs.push(subtask);
+
+        let merge = merge(base, ours, theirs, 4).unwrap();
+        assert_eq!(merge.conflicts, vec![]);
+        assert_eq!(merge.renumbered, vec![(3, 5)]);
+
+        let texts: Vec<_> = merge.tasks.iter().map(|t| (t.id, &t.text[..])).collect();
+        assert_eq!(
+            texts,
+            vec![
+                (1, "Water the cactus"),
+                (2, "Pay rent"),
+                (3, "Call mom"),
+                (4, "Buy bread"),
+                (5, "Buy milk"),
+            ]
+        );
+        assert!(merge.tasks[1].is_completed() && merge.tasks[1].tags.contains("home"));
+        assert_eq!(merge.tasks[3].parent, Some(5));
+    }
+
+    #[test]
+    fn conflicting_changes_keep_ours() {
+        let base = vec![task(1, "Water the plants")];
+        let mut ours = base.clone();
+        ours[0].text = "Water the cactus".to_owned();
+        let mut theirs = base.clone();
+        theirs[0].text = "Water the ferns".to_owned();
+        theirs[0].due = chrono::NaiveDate::from_ymd_opt(2026, 10, 20);
+
+        let merge = merge(base, ours, theirs, 2).unwrap();
+        assert_eq!(
+            merge.conflicts,
+            vec![Conflict {
+                id: 1,
+                field: "text".to_owned()
+            }]
+        );
+        assert_eq!(merge.tasks[0].text, "Water the cactus");
+        assert_eq!(
+            merge.tasks[0].due,
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 20)
+        );
+    }
+
+    #[test]
+    fn ids_freed_by_undo_are_not_reused() {
+        let base = vec![task(1, "Water the plants"), task(2, "Pay rent")];
+        // Ours removed task 2, and added tasks 3 and 4 before undoing the addition of 4.
+        let ours = vec![task(1, "Water the plants"), task(3, "Call mom")];
+        let theirs = vec![
+            task(1, "Water the plants"),
+            task(2, "Pay rent"),
+            task(3, "Buy milk"),
+        ];
+
+        let merge = merge(base, ours, theirs, 5).unwrap();
+        assert_eq!(merge.conflicts, vec![]);
+        assert_eq!(merge.renumbered, vec![(3, 5)]);
+        let ids: Vec<_> = merge.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 3, 5]);
+    }
+}
@@ -0,0 +1,584 @@
+use rusty_journal::dependencies::TreeEntry;
+use rusty_journal::merge::Merge;
+use rusty_journal::search::{self, SearchResult};
+use rusty_journal::stats::Stats;
+use rusty_journal::storage::Backend;
+use rusty_journal::tasks::{Completion, Task};
+use rusty_journal::tracking::{self, HoursMinutes, ReportRow};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable text, aligned in columns.
+    Table,
+    /// Bare values separated by tabs, one record per line (without any headers),
+    /// where backslashes, tabs and line breaks are escaped as `\\`, `\t`, `\n` and `\r`.
+    Plain,
+    /// Pretty-printed JSON.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "plain" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "invalid output format `{}` (expected table, plain or json)",
+                s
+            )),
+        }
+    }
+}
+
+/// Something produced by a subcommand, which can be printed in any output format
+/// (where JSON comes from its `Serialize` implementation).
+pub trait Output: Serialize {
+    fn table(&self) -> String;
+    fn plain(&self) -> String;
+}
+
+pub fn print(output: &impl Output, format: OutputFormat) -> anyhow::Result<()> {
+    let text = match format {
+        OutputFormat::Table => output.table(),
+        OutputFormat::Plain => output.plain(),
+        OutputFormat::Json => serde_json::to_string_pretty(output)?,
+    };
+
+    if !text.is_empty() {
+        println!("{}", text);
+    }
+
+    Ok(())
+}
+
+/// Returns e.g. "1 task" or "3 tasks".
+fn count_tasks(count: usize) -> String {
+    format!("{} {}", count, if count == 1 { "task" } else { "tasks" })
+}
+
+/// Escapes the characters of `s` which would split a value of plain output.
+fn escape_plain(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn plain_task(task: &Task) -> String {
+    let tags: Vec<_> = task.tags.iter().map(|tag| escape_plain(tag)).collect();
+    let or_dash = |s: String| if s.is_empty() { "-".to_owned() } else { s };
+
+    [
+        task.id.to_string(),
+        (if task.is_completed() { "done" } else { "open" }).to_owned(),
+        or_dash(
+            task.due
+                .map(|due| due.format("%F").to_string())
+                .unwrap_or_default(),
+        ),
+        or_dash(task.priority.map(|p| p.to_string()).unwrap_or_default()),
+        or_dash(tags.join(",")),
+        escape_plain(&task.text),
+    ]
+    .join("\t")
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct TaskList(pub Vec<Task>);
+
+impl Output for TaskList {
+    fn table(&self) -> String {
+        let lines: Vec<_> = self
+            .0
+            .iter()
+            .map(|task| format!("{}: {}", task.id, task))
+            .collect();
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        let lines: Vec<_> = self.0.iter().map(plain_task).collect();
+        lines.join("\n")
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct TaskTree(pub Vec<TreeEntry>);
+
+impl Output for TaskTree {
+    fn table(&self) -> String {
+        if self.0.is_empty() {
+            return "The task list is empty".to_owned();
+        }
+
+        let lines: Vec<_> = self
+            .0
+            .iter()
+            .map(|entry| {
+                let indent = "  ".repeat(entry.depth);
+                let mut line = format!("{}{}: {}", indent, entry.task.id, entry.task);
+                if !entry.open_blockers.is_empty() {
+                    let blockers: Vec<_> = entry.open_blockers.iter().map(u64::to_string).collect();
+                    line.push_str(&format!(" (blocked by {})", blockers.join(", ")));
+                }
+                line
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        let lines: Vec<_> = self.0.iter().map(|entry| plain_task(&entry.task)).collect();
+        lines.join("\n")
+    }
+}
+
+/// How a task would be added (by `add --preview`).
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct Preview(pub Task);
+
+impl Output for Preview {
+    fn table(&self) -> String {
+        let task = &self.0;
+        let or_dash = |s: Option<String>| s.unwrap_or_else(|| "-".to_owned());
+        let tags: Vec<_> = task.tags.iter().map(|tag| format!("+{}", tag)).collect();
+
+        let fields = [
+            ("Text", task.text.clone()),
+            ("Priority", or_dash(task.priority.map(|p| p.to_string()))),
+            (
+                "Due",
+                or_dash(task.due.map(|due| due.format("%F (%A)").to_string())),
+            ),
+            (
+                "Tags",
+                or_dash(Some(tags.join(" ")).filter(|tags| !tags.is_empty())),
+            ),
+            (
+                "Repeats",
+                or_dash(task.recurrence.as_ref().map(|r| r.to_string())),
+            ),
+        ];
+        let lines: Vec<_> = fields
+            .iter()
+            .map(|(name, value)| format!("{:<9} {}", format!("{}:", name), value))
+            .collect();
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        plain_task(&self.0)
+    }
+}
+
+/// A task which was added or changed, e.g. "Added task 3".
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct TaskChange {
+    #[serde(skip)]
+    pub verb: &'static str,
+    pub task: Task,
+}
+
+impl Output for TaskChange {
+    fn table(&self) -> String {
+        format!("{} task {}", self.verb, self.task.id)
+    }
+
+    fn plain(&self) -> String {
+        self.task.id.to_string()
+    }
+}
+
+impl Output for Completion {
+    fn table(&self) -> String {
+        let mut text = format!("Completed task {}", self.task.id);
+        if let Some(next) = &self.next {
+            let due = next.due.map(|due| due.format("%F").to_string());
+            text.push_str(&format!(
+                "\nNext occurrence scheduled as task {}, due {}",
+                next.id,
+                due.unwrap_or_default()
+            ));
+        }
+        text
+    }
+
+    fn plain(&self) -> String {
+        let mut ids = vec![self.task.id.to_string()];
+        ids.extend(self.next.iter().map(|next| next.id.to_string()));
+        ids.join("\n")
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct Stopped(pub Task);
+
+impl Output for Stopped {
+    fn table(&self) -> String {
+        let tracked = tracking::tracked_time(&self.0, chrono::Utc::now());
+        format!(
+            "Stopped task {} ({} tracked in total)",
+            self.0.id,
+            HoursMinutes(tracked.num_seconds())
+        )
+    }
+
+    fn plain(&self) -> String {
+        self.0.id.to_string()
+    }
+}
+
+/// A number of tasks which were affected, e.g. "Imported 3 tasks".
+#[derive(Serialize)]
+pub struct TaskCount {
+    #[serde(skip)]
+    pub verb: &'static str,
+    pub count: usize,
+}
+
+impl Output for TaskCount {
+    fn table(&self) -> String {
+        format!("{} {}", self.verb, count_tasks(self.count))
+    }
+
+    fn plain(&self) -> String {
+        self.count.to_string()
+    }
+}
+
+/// The outcome of a subcommand which has nothing else to show.
+#[derive(Serialize)]
+pub struct Message {
+    pub message: String,
+}
+
+impl Output for Message {
+    fn table(&self) -> String {
+        self.message.clone()
+    }
+
+    fn plain(&self) -> String {
+        String::new()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct SearchResults {
+    pub results: Vec<SearchResult>,
+    #[serde(skip)]
+    pub use_color: bool,
+}
+
+impl Output for SearchResults {
+    fn table(&self) -> String {
+        if self.results.is_empty() {
+            return "No tasks match".to_owned();
+        }
+
+        let highlight = |s: &str, field: &Option<search::FieldMatch>| match field {
+            Some(field) if self.use_color => search::highlight(s, &field.ranges),
+            _ => s.to_owned(),
+        };
+
+        let lines: Vec<_> = self
+            .results
+            .iter()
+            .map(|result| {
+                let task = &result.task;
+                let mut line = format!("{}: {}", task.id, highlight(&task.text, &result.text));
+                for (tag, tag_match) in task.tags.iter().zip(&result.tags) {
+                    line.push_str(&format!(" +{}", highlight(tag, tag_match)));
+                }
+                if task.is_completed() {
+                    line.push_str(" (done)");
+                }
+                line
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        let lines: Vec<_> = self
+            .results
+            .iter()
+            .map(|result| format!("{}\t{}", result.score, plain_task(&result.task)))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct TimeReport(pub Vec<ReportRow>);
+
+impl Output for TimeReport {
+    fn table(&self) -> String {
+        if self.0.is_empty() {
+            return "No time was tracked".to_owned();
+        }
+
+        let width = self.0.iter().map(|row| row.group.chars().count()).max();
+        let width = width.unwrap_or(0).max("total".len());
+        let total: i64 = self.0.iter().map(|row| row.seconds).sum();
+
+        let mut lines: Vec<_> = self
+            .0
+            .iter()
+            .map(|row| format!("{:<width$}  {:>8}", row.group, HoursMinutes(row.seconds)))
+            .collect();
+        lines.push(format!("{:<width$}  {:>8}", "total", HoursMinutes(total)));
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        let lines: Vec<_> = self
+            .0
+            .iter()
+            .map(|row| format!("{}\t{}", escape_plain(&row.group), row.seconds))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+#[derive(Serialize)]
+pub struct NamedJournal {
+    pub name: String,
+    pub path: PathBuf,
+    pub backend: Backend,
+    pub default: bool,
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct JournalList(pub Vec<NamedJournal>);
+
+impl Output for JournalList {
+    fn table(&self) -> String {
+        if self.0.is_empty() {
+            return "There are no named journals".to_owned();
+        }
+
+        let lines: Vec<_> = self
+            .0
+            .iter()
+            .map(|journal| {
+                format!(
+                    "{}{}: {} [{}]",
+                    journal.name,
+                    if journal.default { " (default)" } else { "" },
+                    journal.path.display(),
+                    journal.backend
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        let lines: Vec<_> = self
+            .0
+            .iter()
+            .map(|journal| {
+                format!(
+                    "{}\t{}\t{}",
+                    escape_plain(&journal.name),
+                    escape_plain(&journal.path.to_string_lossy()),
+                    journal.backend
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+/// Formats a number of seconds as e.g. "3d 4h", or "4h 05m" when under a day.
+fn days_hours(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    if hours < 24 {
+        HoursMinutes(seconds).to_string()
+    } else {
+        format!("{}d {}h", hours / 24, hours % 24)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct StatsReport(pub Stats);
+
+impl Output for StatsReport {
+    fn table(&self) -> String {
+        let stats = &self.0;
+        let mut lines = vec!["Completed per week:".to_owned()];
+        lines.extend(
+            stats
+                .throughput
+                .iter()
+                .map(|week| format!("  {}  {:>4}", week.week, week.completed)),
+        );
+        lines.push(format!(
+            "Average time to complete: {}",
+            stats
+                .average_completion_seconds
+                .map_or_else(|| "-".to_owned(), days_hours)
+        ));
+        lines.push(format!(
+            "Open: {} ({} overdue)",
+            count_tasks(stats.open),
+            stats.overdue
+        ));
+        lines.push(format!(
+            "Completed: {} ({} late)",
+            count_tasks(stats.completed),
+            stats.completed_late
+        ));
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        let stats = &self.0;
+        let mut lines: Vec<_> = stats
+            .throughput
+            .iter()
+            .map(|week| format!("{}\t{}", week.week, week.completed))
+            .collect();
+        lines.push(format!(
+            "average_completion_seconds\t{}",
+            stats
+                .average_completion_seconds
+                .map_or_else(|| "-".to_owned(), |seconds| seconds.to_string())
+        ));
+        lines.push(format!("open\t{}", stats.open));
+        lines.push(format!("overdue\t{}", stats.overdue));
+        lines.push(format!("completed\t{}", stats.completed));
+        lines.push(format!("completed_late\t{}", stats.completed_late));
+        lines.join("\n")
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct MergeReport(pub Merge);
+
+impl Output for MergeReport {
+    fn table(&self) -> String {
+        let merge = &self.0;
+        let mut lines = vec![format!("Merged {}", count_tasks(merge.tasks.len()))];
+        lines.extend(
+            merge
+                .renumbered
+                .iter()
+                .map(|(old, new)| format!("Their task {} is now task {}", old, new)),
+        );
+        lines.extend(merge.conflicts.iter().map(|conflict| {
+            format!(
+                "Conflict in the {} of task {} (kept ours)",
+                conflict.field, conflict.id
+            )
+        }));
+        lines.join("\n")
+    }
+
+    fn plain(&self) -> String {
+        let lines: Vec<_> = self
+            .0
+            .conflicts
+            .iter()
+            .map(|conflict| format!("{}\t{}", conflict.id, conflict.field))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use rusty_journal::tasks::Priority;
+
+    fn tasks() -> TaskList {
+        let mut rent = Task::new("Pay rent".to_owned());
+        rent.id = 1;
+        rent.created_at = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        rent.priority = Some(Priority::High);
+        rent.due = NaiveDate::from_ymd_opt(2026, 11, 1);
+        rent.tags.insert("home".to_owned());
+
+        let mut call = Task::new("Call mom".to_owned());
+        call.id = 2;
+        call.created_at = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        call.completed_at = Some(Utc.with_ymd_and_hms(2026, 10, 17, 8, 0, 0).unwrap());
+
+        TaskList(vec![rent, call])
+    }
+
+    #[test]
+    fn task_lists() {
+        let tasks = tasks();
+        let table = tasks.table();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("1: ") && lines[0].contains("Pay rent"));
+        assert!(lines[1].starts_with("2: ") && lines[1].contains("Call mom"));
+
+        assert_eq!(
+            tasks.plain(),
+            "1\topen\t2026-11-01\thigh\thome\tPay rent\n2\tdone\t-\t-\t-\tCall mom"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&tasks).unwrap()).unwrap();
+        assert_eq!(json[0]["id"], 1);
+        assert_eq!(json[0]["text"], "Pay rent");
+        assert_eq!(json[1]["text"], "Call mom");
+    }
+
+    #[test]
+    fn plain_values_are_escaped() {
+        let mut tasks = tasks();
+        tasks.0.truncate(1);
+        tasks.0[0].text = "Pay\trent\r\nnow \\o/".to_owned();
+        tasks.0[0].tags.insert("a\tb".to_owned());
+
+        let plain = tasks.plain();
+        assert_eq!(plain.lines().count(), 1);
+        assert_eq!(plain.split('\t').count(), 6);
+        assert!(plain.ends_with("\ta\\tb,home\tPay\\trent\\r\\nnow \\\\o/"));
+    }
+
+    #[test]
+    fn time_reports() {
+        let report = TimeReport(vec![ReportRow {
+            group: "home".to_owned(),
+            seconds: 5400,
+        }]);
+        assert_eq!(report.plain(), "home\t5400");
+        let table = report.table();
+        assert_eq!(table.lines().count(), 2);
+        assert!(table.starts_with("home") && table.contains("total"));
+        assert_eq!(TimeReport(vec![]).table(), "No time was tracked");
+    }
+}
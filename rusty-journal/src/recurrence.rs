@@ -0,0 +1,250 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How often a task repeats, once it's done.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "every", rename_all = "lowercase")]
+pub enum Recurrence {
+    /// Every day.
+    Daily,
+    /// Every week, on the given weekdays (or on the weekday of the due date, if empty).
+    Weekly { weekdays: Vec<Weekday> },
+    /// Every month, on the given day (or on the day of the due date, if `None`).
+    ///
+    /// @Note: in months which are too short for `day`, the task is due on their
+    /// last day instead (e.g. a task due on the 31st is due on February 28th).
+    Monthly { day: Option<u32> },
+    /// Every `count` days.
+    Days { count: u32 },
+}
+
+impl Recurrence {
+    /// Fill in the weekday or day of the month this recurrence is relative to
+    /// (when they weren't given explicitly), using the task's first due `date`.
+    ///
+    /// This keeps e.g. a monthly task due on the 31st on the last day of every
+    /// month, instead of drifting to the 28th after going through February.
+    pub fn anchored(self, date: NaiveDate) -> Self {
+        match self {
+            Recurrence::Weekly { weekdays } if weekdays.is_empty() => Recurrence::Weekly {
+                weekdays: vec![date.weekday()],
+            },
+            Recurrence::Monthly { day: None } => Recurrence::Monthly {
+                day: Some(date.day()),
+            },
+            recurrence => recurrence,
+        }
+    }
+
+    /// Returns the first day after `date` on which the task is due again.
+    pub fn next_after(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Recurrence::Daily => date + Duration::days(1),
+            Recurrence::Weekly { weekdays } if weekdays.is_empty() => date + Duration::days(7),
+            Recurrence::Weekly { weekdays } => (1..=7)
+                .map(|days| date + Duration::days(days))
+                .find(|next| weekdays.contains(&next.weekday()))
+                .unwrap(), // every weekday happens once in any 7 consecutive days
+            Recurrence::Monthly { day } => {
+                let day = day.unwrap_or_else(|| date.day());
+                let this_month = day_of_month(date.year(), date.month(), day);
+                if this_month > date {
+                    this_month
+                } else if date.month() == 12 {
+                    day_of_month(date.year() + 1, 1, day)
+                } else {
+                    day_of_month(date.year(), date.month() + 1, day)
+                }
+            }
+            Recurrence::Days { count } => date + Duration::days(i64::from(*count).max(1)),
+        }
+    }
+}
+
+/// Returns the `day` of the given month, clamped to the month's last day.
+fn day_of_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day.clamp(1, 31))
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .unwrap() // every month has at least 28 days
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    /// Parse one of `daily`, `weekly`, `weekly:mon,thu`, `monthly`, `monthly:31` or
+    /// `3d` (i.e. every 3 days).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let (kind, arguments) = match s.split_once(':') {
+            Some((kind, arguments)) => (kind, Some(arguments)),
+            None => (s.as_str(), None),
+        };
+
+        let invalid = || {
+            format!(
+                "invalid recurrence `{}` (expected daily, weekly[:mon,thu], monthly[:31] or e.g. 3d)",
+                s
+            )
+        };
+
+        match (kind, arguments) {
+            ("daily" | "day", None) => Ok(Recurrence::Daily),
+            ("weekly" | "week", None) => Ok(Recurrence::Weekly {
+                weekdays: Vec::new(),
+            }),
+            ("weekly" | "week", Some(weekdays)) => {
+                let mut weekdays = weekdays
+                    .split(',')
+                    .map(|weekday| weekday.trim().parse::<Weekday>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| invalid())?;
+                weekdays.sort_by_key(|weekday| weekday.num_days_from_monday());
+                weekdays.dedup();
+                Ok(Recurrence::Weekly { weekdays })
+            }
+            ("monthly" | "month", None) => Ok(Recurrence::Monthly { day: None }),
+            ("monthly" | "month", Some(day)) => match day.parse() {
+                Ok(day) if (1..=31).contains(&day) => Ok(Recurrence::Monthly { day: Some(day) }),
+                _ => Err(invalid()),
+            },
+            (days, None) => days
+                .strip_suffix("days")
+                .or_else(|| days.strip_suffix('d'))
+                .unwrap_or(days)
+                .parse()
+                .ok()
+                .filter(|&count| count > 0)
+                .map(|count| Recurrence::Days { count })
+                .ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly { weekdays } if weekdays.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly { weekdays } => {
+                let weekdays: Vec<_> = weekdays.iter().map(|&w| weekday_name(w)).collect();
+                write!(f, "weekly:{}", weekdays.join(","))
+            }
+            Recurrence::Monthly { day: None } => write!(f, "monthly"),
+            Recurrence::Monthly { day: Some(day) } => write!(f, "monthly:{}", day),
+            Recurrence::Days { count } => write!(f, "{}d", count),
+        }
+    }
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for s in &[
+            "daily",
+            "weekly",
+            "weekly:mon,thu",
+            "monthly",
+            "monthly:31",
+            "3d",
+        ] {
+            assert_eq!(s.parse::<Recurrence>().unwrap().to_string(), *s);
+        }
+        assert_eq!("10days".parse(), Ok(Recurrence::Days { count: 10 }));
+        assert!("monthly:32".parse::<Recurrence>().is_err());
+        assert!("weekly:someday".parse::<Recurrence>().is_err());
+        assert!("0d".parse::<Recurrence>().is_err());
+        assert!("3dd".parse::<Recurrence>().is_err());
+        assert!("3daysdays".parse::<Recurrence>().is_err());
+        assert_eq!("3days".parse(), Ok(Recurrence::Days { count: 3 }));
+    }
+
+    #[test]
+    fn daily_and_every_n_days() {
+        assert_eq!(
+            Recurrence::Daily.next_after(date(2026, 12, 31)),
+            date(2027, 1, 1)
+        );
+        let every_ten_days = Recurrence::Days { count: 10 };
+        assert_eq!(
+            every_ten_days.next_after(date(2026, 2, 25)),
+            date(2026, 3, 7)
+        );
+    }
+
+    #[test]
+    fn weekly_on_weekdays() {
+        let recurrence: Recurrence = "weekly:mon,thu".parse().unwrap();
+        // 2026-10-16 is a friday.
+        assert_eq!(
+            recurrence.next_after(date(2026, 10, 16)),
+            date(2026, 10, 19)
+        );
+        assert_eq!(
+            recurrence.next_after(date(2026, 10, 19)),
+            date(2026, 10, 22)
+        );
+
+        let anchored = Recurrence::Weekly { weekdays: vec![] }.anchored(date(2026, 10, 16));
+        assert_eq!(anchored.next_after(date(2026, 10, 16)), date(2026, 10, 23));
+    }
+
+    #[test]
+    fn monthly_at_the_end_of_the_month() {
+        let recurrence = Recurrence::Monthly { day: None }.anchored(date(2026, 1, 31));
+
+        let mut due = date(2026, 1, 31);
+        let mut dues = Vec::new();
+        for _ in 0..4 {
+            due = recurrence.next_after(due);
+            dues.push(due);
+        }
+        assert_eq!(
+            dues,
+            vec![
+                date(2026, 2, 28),
+                date(2026, 3, 31),
+                date(2026, 4, 30),
+                date(2026, 5, 31)
+            ]
+        );
+
+        // Leap years and year boundaries.
+        let on_the_30th = Recurrence::Monthly { day: Some(30) };
+        assert_eq!(on_the_30th.next_after(date(2028, 1, 30)), date(2028, 2, 29));
+        assert_eq!(
+            on_the_30th.next_after(date(2026, 12, 30)),
+            date(2027, 1, 30)
+        );
+        assert_eq!(
+            on_the_30th.next_after(date(2026, 12, 1)),
+            date(2026, 12, 30)
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::storage::{sibling_path, write_atomically, Journal};
+use crate::tasks::{self, JournalLock, Task};
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Where reminders are delivered to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// Run a shell command once per task, with the task's details in `RUSTY_JOURNAL_*`
+    /// environment variables, and as JSON on its standard input.
+    Command(String),
+    /// Append a line per task to a file.
+    File(PathBuf),
+    /// Nothing besides returning the tasks (e.g. so that they're printed).
+    None,
+}
+
+/// Reminders already delivered, as the due date each task was reminded of (so
+/// that a task is reminded again if its due date changes).
+type Delivered = BTreeMap<u64, NaiveDate>;
+
+/// Path of the file tracking delivered reminders, which sits next to the journal.
+fn delivered_path(journal_path: &Path) -> PathBuf {
+    sibling_path(journal_path, ".reminders")
+}
+
+fn load_delivered(path: &Path) -> io::Result<Delivered> {
+    match fs::read(path) {
+        Ok(content) => Ok(serde_json::from_slice(&content)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Delivered::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the open tasks due up to `within_days` days after `today` (overdue
+/// ones included) which weren't reminded of yet.
+fn pending<'a>(
+    tasks: &'a [Task],
+    delivered: &Delivered,
+    today: NaiveDate,
+    within_days: u32,
+) -> Vec<&'a Task> {
+    let last_day = today + Duration::days(i64::from(within_days));
+
+    let mut pending: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| !task.is_completed())
+        .filter(|task| {
+            task.due
+                .is_some_and(|due| due <= last_day && delivered.get(&task.id) != Some(&due))
+        })
+        .collect();
+    pending.sort_by_key(|task| (task.due, task.id));
+    pending
+}
+
+fn run_command(command: &str, task: &Task) -> io::Result<()> {
+    let tags: Vec<_> = task.tags.iter().cloned().collect();
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("RUSTY_JOURNAL_ID", task.id.to_string())
+        .env("RUSTY_JOURNAL_TEXT", &task.text)
+        .env(
+            "RUSTY_JOURNAL_DUE",
+            task.due
+                .map(|due| due.format("%F").to_string())
+                .unwrap_or_default(),
+        )
+        .env(
+            "RUSTY_JOURNAL_PRIORITY",
+            task.priority.map(|p| p.to_string()).unwrap_or_default(),
+        )
+        .env("RUSTY_JOURNAL_TAGS", tags.join(","))
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    // @Note: commands which don't read their input close the pipe early, which
+    // isn't an error.
+    if let Some(mut stdin) = child.stdin.take() {
+        match serde_json::to_writer(&mut stdin, task) {
+            Err(e) if e.io_error_kind() != Some(ErrorKind::BrokenPipe) => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "The reminder command failed for task {} ({})",
+            task.id, status
+        )))
+    }
+}
+
+fn append_to_file(path: &Path, task: &Task) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let due = task.due.map(|due| due.format("%F").to_string());
+    writeln!(
+        file,
+        "{}\t{}\t{}",
+        due.unwrap_or_default(),
+        task.id,
+        task.text
+    )
+}
+
+/// Deliver a reminder through `hook` for every open task due up to `within_days`
+/// days after `today` which wasn't reminded of yet, returning those tasks.
+///
+/// Meant to run periodically (e.g. from cron), so the journal stays locked while
+/// reminders are delivered, and a failed delivery is retried on the next run.
+pub fn remind(
+    journal: &Journal,
+    hook: &Hook,
+    today: NaiveDate,
+    within_days: u32,
+) -> io::Result<Vec<Task>> {
+    let _lock = JournalLock::acquire(&journal.path)?;
+
+    let tasks = tasks::load_tasks(journal)?;
+    let delivered_path = delivered_path(&journal.path);
+    let mut delivered = load_delivered(&delivered_path)?;
+
+    let mut reminded = Vec::new();
+    let mut result = Ok(());
+    for task in pending(&tasks, &delivered, today, within_days) {
+        result = match hook {
+            Hook::Command(command) => run_command(command, task),
+            Hook::File(path) => append_to_file(path, task),
+            Hook::None => Ok(()),
+        };
+        if result.is_err() {
+            break;
+        }
+
+        delivered.insert(task.id, task.due.unwrap());
+        reminded.push(task.clone());
+    }
+
+    // @Note: tasks which are completed (or gone) won't be reminded of again.
+    delivered.retain(|id, _| tasks.iter().any(|t| t.id == *id && !t.is_completed()));
+    write_atomically(&delivered_path, &serde_json::to_vec(&delivered)?)?;
+
+    result.map(|()| reminded)
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Backend;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn task(id: u64, due: Option<u32>) -> Task {
+        let mut task = Task::new(format!("task {}", id));
+        task.id = id;
+        task.due = due.map(date);
+        task
+    }
+
+    #[test]
+    fn pending_reminders() {
+        let mut tasks = vec![
+            task(1, Some(14)), // overdue
+            task(2, Some(17)),
+            task(3, Some(19)), // outside the window
+            task(4, None),
+            task(5, Some(16)),
+            task(6, Some(16)),
+        ];
+        tasks[4].completed_at = Some(tasks[4].created_at);
+
+        let mut delivered = Delivered::new();
+        delivered.insert(6, date(16));
+        let ids = |delivered: &Delivered| -> Vec<u64> {
+            pending(&tasks, delivered, date(16), 2)
+                .iter()
+                .map(|task| task.id)
+                .collect()
+        };
+        assert_eq!(ids(&delivered), vec![1, 2]);
+
+        // Tasks are reminded of again when their due date changes.
+        delivered.insert(6, date(15));
+        assert_eq!(ids(&delivered), vec![1, 6, 2]);
+    }
+
+    #[test]
+    fn delivery() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::new(dir.path().join("journal.json"), Backend::Json);
+        for due in [16, 17] {
+            tasks::add_task(&journal, task(0, Some(due))).unwrap();
+        }
+        let remind_with = |command: &str| -> io::Result<Vec<u64>> {
+            let hook = Hook::Command(command.to_owned());
+            let reminded = remind(&journal, &hook, date(16), 1)?;
+            Ok(reminded.iter().map(|task| task.id).collect())
+        };
+        let delivered = || load_delivered(&delivered_path(&journal.path)).unwrap();
+
+        // A failed delivery stops the following ones, and is retried on the next run.
+        assert!(remind_with("false").is_err());
+        assert_eq!(delivered(), Delivered::new());
+        assert!(remind_with(r#"test "$RUSTY_JOURNAL_ID" = 1"#).is_err());
+        assert_eq!(delivered(), vec![(1, date(16))].into_iter().collect());
+        assert_eq!(remind_with("true").unwrap(), vec![2]);
+
+        // Delivered reminders are skipped (the command would fail otherwise).
+        assert_eq!(remind_with("false").unwrap(), Vec::<u64>::new());
+
+        // Tasks are reminded of again when their due date changes.
+        tasks::edit_task(&journal, 1, |task| task.due = Some(date(17))).unwrap();
+        assert_eq!(remind_with("true").unwrap(), vec![1]);
+
+        let path = dir.path().join("reminders.txt");
+        tasks::edit_task(&journal, 2, |task| task.due = Some(date(16))).unwrap();
+        remind(&journal, &Hook::File(path.clone()), date(16), 1).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "2026-10-16\t2\ttask 0\n");
+    }
+}
@@ -0,0 +1,292 @@
+use crate::tasks::Task;
+use serde::Serialize;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The query must appear as is.
+    Substring,
+    /// The query must appear, ignoring differences in case.
+    IgnoreCase,
+    /// The characters of the query must appear in order (though not necessarily
+    /// next to each other), ignoring case. Results are ranked by how good a match
+    /// they are, e.g. `wtpl` matches "Water the plants".
+    Fuzzy,
+}
+
+/// Where a field of a task matched the query, as byte ranges into it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldMatch {
+    pub score: i64,
+    pub ranges: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub task: Task,
+    pub score: i64,
+    /// Matches in the task's text (if any).
+    pub text: Option<FieldMatch>,
+    /// Matches in each of the task's tags (in the same order as `task.tags`).
+    pub tags: Vec<Option<FieldMatch>>,
+}
+
+/// Returns the tasks whose text or tags match `query`, best matches first.
+///
+/// An empty query matches nothing, whatever the mode.
+pub fn search(
+    tasks: impl IntoIterator<Item = Task>,
+    query: &str,
+    mode: MatchMode,
+) -> Vec<SearchResult> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut results: Vec<SearchResult> = tasks
+        .into_iter()
+        .filter_map(|task| {
+            let text = match_field(&task.text, query, mode);
+            let tags: Vec<_> = task
+                .tags
+                .iter()
+                .map(|tag| match_field(tag, query, mode))
+                .collect();
+
+            let score = text
+                .iter()
+                .chain(tags.iter().flatten())
+                .map(|field| field.score)
+                .max()?;
+
+            Some(SearchResult {
+                task,
+                score,
+                text,
+                tags,
+            })
+        })
+        .collect();
+
+    // @Note: the sort is stable, so tasks that match equally well keep their order.
+    results.sort_by_key(|result| std::cmp::Reverse(result.score));
+    results
+}
+
+fn match_field(haystack: &str, query: &str, mode: MatchMode) -> Option<FieldMatch> {
+    match mode {
+        MatchMode::Substring => find_substrings(haystack, query, false),
+        MatchMode::IgnoreCase => find_substrings(haystack, query, true),
+        MatchMode::Fuzzy => find_fuzzy(haystack, query),
+    }
+}
+
+/// Case folding of a single character (good enough for matching purposes).
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Finds every non-overlapping occurrence of `query` in `haystack`.
+fn find_substrings(haystack: &str, query: &str, ignore_case: bool) -> Option<FieldMatch> {
+    let normalize = |c: char| if ignore_case { fold(c) } else { c };
+
+    let chars: Vec<(usize, char)> = haystack.char_indices().collect();
+    let query: Vec<char> = query.chars().map(normalize).collect();
+
+    if query.is_empty() {
+        return Some(FieldMatch {
+            score: 0,
+            ranges: Vec::new(),
+        });
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start + query.len() <= chars.len() {
+        let window = &chars[start..start + query.len()];
+        if window
+            .iter()
+            .map(|&(_, c)| normalize(c))
+            .eq(query.iter().copied())
+        {
+            let (last_index, last_char) = window[window.len() - 1];
+            ranges.push(window[0].0..last_index + last_char.len_utf8());
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+
+    if ranges.is_empty() {
+        None
+    } else {
+        Some(FieldMatch {
+            score: ranges.len() as i64,
+            ranges,
+        })
+    }
+}
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 16;
+const WORD_START_BONUS: i64 = 8;
+const GAP_PENALTY: i64 = 1;
+
+/// Matches the characters of `query` in order, preferring consecutive characters
+/// and characters at the start of words, and penalizing gaps between them.
+fn find_fuzzy(haystack: &str, query: &str) -> Option<FieldMatch> {
+    let chars: Vec<(usize, char)> = haystack.char_indices().collect();
+    let query: Vec<char> = query.chars().map(fold).collect();
+
+    let first = *query.first()?;
+    let is_word_start =
+        |i: usize| i == 0 || !chars[i - 1].1.is_alphanumeric() && chars[i].1.is_alphanumeric();
+
+    // @Note: a greedy match is done from every position where the first character
+    // of the query appears, keeping the best one. This isn't guaranteed to find the
+    // optimal alignment, but it's simple and works well for short task texts.
+    (0..chars.len())
+        .filter(|&start| fold(chars[start].1) == first)
+        .filter_map(|start| {
+            let mut matched = vec![start];
+            let mut query_chars = query[1..].iter();
+            let mut wanted = query_chars.next();
+
+            for (i, &(_, c)) in chars.iter().enumerate().skip(start + 1) {
+                match wanted {
+                    Some(&q) if fold(c) == q => {
+                        matched.push(i);
+                        wanted = query_chars.next();
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            if wanted.is_some() {
+                return None;
+            }
+
+            let mut score = 0;
+            for (k, &i) in matched.iter().enumerate() {
+                score += MATCH_SCORE;
+                if is_word_start(i) {
+                    score += WORD_START_BONUS;
+                }
+                if k > 0 {
+                    let gap = (i - matched[k - 1] - 1) as i64;
+                    score += if gap == 0 {
+                        CONSECUTIVE_BONUS
+                    } else {
+                        -GAP_PENALTY * gap
+                    };
+                }
+            }
+
+            Some((score, matched))
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(score, matched)| FieldMatch {
+            score,
+            ranges: merge_ranges(
+                matched
+                    .into_iter()
+                    .map(|i| chars[i].0..chars[i].0 + chars[i].1.len_utf8()),
+            ),
+        })
+}
+
+/// Joins adjacent ranges, so that highlights span whole runs of characters.
+fn merge_ranges(ranges: impl Iterator<Item = Range<usize>>) -> Vec<Range<usize>> {
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+const HIGHLIGHT_START: &str = "\x1b[1;31m"; // bold red
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+/// Returns `s` with the given byte `ranges` highlighted using ANSI escape codes.
+pub fn highlight(s: &str, ranges: &[Range<usize>]) -> String {
+    let mut highlighted = String::with_capacity(s.len());
+    let mut last_end = 0;
+    for range in ranges {
+        highlighted.push_str(&s[last_end..range.start]);
+        highlighted.push_str(HIGHLIGHT_START);
+        highlighted.push_str(&s[range.clone()]);
+        highlighted.push_str(HIGHLIGHT_END);
+        last_end = range.end;
+    }
+    highlighted.push_str(&s[last_end..]);
+    highlighted
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(haystack: &str, query: &str, mode: MatchMode) -> Option<Vec<Range<usize>>> {
+        match_field(haystack, query, mode).map(|field| field.ranges)
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn substrings() {
+        let text = "Read the README";
+        assert_eq!(ranges(text, "Re", MatchMode::Substring), Some(vec![0..2]));
+        assert_eq!(
+            ranges(text, "re", MatchMode::IgnoreCase),
+            Some(vec![0..2, 9..11])
+        );
+        assert_eq!(ranges(text, "write", MatchMode::IgnoreCase), None);
+
+        // Byte ranges of multi-byte characters.
+        assert_eq!(
+            ranges("Café CAFÉ", "café", MatchMode::IgnoreCase),
+            Some(vec![0..5, 6..11])
+        );
+    }
+
+    #[test]
+    fn fuzzy_matches() {
+        assert_eq!(
+            ranges("Water the plants", "wtpl", MatchMode::Fuzzy),
+            Some(vec![0..1, 2..3, 10..12])
+        );
+        assert_eq!(ranges("Water the plants", "plw", MatchMode::Fuzzy), None);
+    }
+
+    #[test]
+    fn empty_query() {
+        let tasks = vec![Task::new("Water the plants".to_owned())];
+        for mode in [
+            MatchMode::Substring,
+            MatchMode::IgnoreCase,
+            MatchMode::Fuzzy,
+        ] {
+            assert!(search(tasks.clone(), "", mode).is_empty());
+        }
+    }
+
+    #[test]
+    fn fuzzy_ranking() {
+        let tasks: Vec<Task> = ["Fix the leak", "Reflow the text", "Call Flo"]
+            .iter()
+            .map(|text| Task::new(text.to_string()))
+            .collect();
+
+        let results = search(tasks, "fl", MatchMode::Fuzzy);
+        let texts: Vec<_> = results.iter().map(|result| &result.task.text[..]).collect();
+
+        // Consecutive characters at the start of a word rank best.
+        assert_eq!(texts, vec!["Call Flo", "Reflow the text", "Fix the leak"]);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::tasks::Task;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde::Serialize;
+
+/// Number of tasks completed during one (ISO) week.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WeekThroughput {
+    pub week: String,
+    pub completed: usize,
+}
+
+/// Statistics over every task in a journal, including archived ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    /// Tasks completed in each of the last weeks, oldest first.
+    pub throughput: Vec<WeekThroughput>,
+    /// Average time it took to complete a task, from when it was created.
+    pub average_completion_seconds: Option<i64>,
+    pub open: usize,
+    /// Open tasks which are past their due date.
+    pub overdue: usize,
+    pub completed: usize,
+    /// Completed tasks which were done after their due date.
+    pub completed_late: usize,
+}
+
+fn week_name(day: NaiveDate) -> String {
+    let week = day.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Computes the statistics of `tasks` (in the given `timezone`), showing the
+/// throughput of the `weeks` weeks up to (and including) the one of `today`.
+pub fn stats_in<Tz: TimeZone>(
+    tasks: &[Task],
+    weeks: usize,
+    today: NaiveDate,
+    timezone: &Tz,
+) -> Stats {
+    let local_date = |time: DateTime<Utc>| time.with_timezone(timezone).date_naive();
+
+    let completed: Vec<_> = tasks
+        .iter()
+        .filter_map(|task| task.completed_at.map(|completed_at| (task, completed_at)))
+        .collect();
+
+    let throughput = (0..weeks)
+        .rev()
+        .map(|weeks_ago| week_name(today - Duration::weeks(weeks_ago as i64)))
+        .map(|week| WeekThroughput {
+            completed: completed
+                .iter()
+                .filter(|(_, completed_at)| week_name(local_date(*completed_at)) == week)
+                .count(),
+            week,
+        })
+        .collect();
+
+    let total_seconds: i64 = completed
+        .iter()
+        .map(|(task, completed_at)| (*completed_at - task.created_at).num_seconds().max(0))
+        .sum();
+
+    Stats {
+        throughput,
+        average_completion_seconds: match completed.len() {
+            0 => None,
+            count => Some(total_seconds / count as i64),
+        },
+        open: tasks.len() - completed.len(),
+        overdue: tasks.iter().filter(|task| task.is_overdue(today)).count(),
+        completed: completed.len(),
+        completed_late: completed
+            .iter()
+            .filter(|(task, completed_at)| {
+                task.due.is_some_and(|due| local_date(*completed_at) > due)
+            })
+            .count(),
+    }
+}
+
+/// Same as `stats_in`, using the local timezone and the current date.
+pub fn stats(tasks: &[Task], weeks: usize) -> Stats {
+    stats_in(tasks, weeks, Local::now().date_naive(), &Local)
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap()
+    }
+
+    fn task(created: DateTime<Utc>, completed: Option<DateTime<Utc>>, due: Option<u32>) -> Task {
+        let mut task = Task::new("task".to_owned());
+        task.created_at = created;
+        task.completed_at = completed;
+        task.due = due.and_then(|day| NaiveDate::from_ymd_opt(2026, 10, day));
+        task
+    }
+
+    #[test]
+    fn throughput_averages_and_overdue_counts() {
+        let tasks = vec![
+            task(at(5, 9), Some(at(6, 9)), Some(7)),
+            task(at(5, 9), Some(at(14, 9)), Some(10)), // completed late
+            task(at(12, 9), Some(at(15, 21)), None),
+            task(at(12, 9), None, Some(13)), // overdue
+            task(at(12, 9), None, Some(20)),
+        ];
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+
+        let stats = stats_in(&tasks, 3, today, &Utc);
+        let throughput: Vec<_> = stats
+            .throughput
+            .iter()
+            .map(|week| (&week.week[..], week.completed))
+            .collect();
+        assert_eq!(
+            throughput,
+            vec![("2026-W40", 0), ("2026-W41", 1), ("2026-W42", 2)]
+        );
+
+        // (1 + 9 + 3.5 days) / 3
+        assert_eq!(
+            stats.average_completion_seconds,
+            Some(4 * 86400 + 12 * 3600)
+        );
+        assert_eq!((stats.open, stats.overdue), (2, 1));
+        assert_eq!((stats.completed, stats.completed_late), (3, 1));
+    }
+}
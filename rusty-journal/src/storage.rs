@@ -0,0 +1,224 @@
+use crate::tasks::Task;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+mod json;
+mod jsonl;
+mod keyed;
+mod sqlite;
+
+pub use json::JsonStorage;
+pub use jsonl::JsonLinesStorage;
+pub use keyed::KeyedJsonStorage;
+pub use sqlite::SqliteStorage;
+
+/// Everything a journal keeps.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Contents {
+    pub tasks: Vec<Task>,
+    /// The ID given to the next task added, which never goes back down, so that
+    /// the IDs of removed (e.g. undone) tasks aren't given to new ones.
+    ///
+    /// @Note: journals written before it was kept have it at 0.
+    pub next_id: u64,
+}
+
+/// A place where the tasks of a journal are persisted.
+pub trait Storage {
+    /// Read the whole journal (an empty journal if it doesn't exist yet).
+    fn load(&self) -> io::Result<Contents>;
+
+    /// Make `contents` the new contents of the journal.
+    fn save(&mut self, contents: &Contents) -> io::Result<()>;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// A single JSON object with every task.
+    #[default]
+    Json,
+    /// An append-only log of changes, with one JSON event per line.
+    Jsonl,
+    /// A SQLite database file.
+    Sqlite,
+    /// A JSON object with one task per line, keyed by ID (for syncing with git).
+    Git,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Backend::Json),
+            "jsonl" | "json-lines" => Ok(Backend::Jsonl),
+            "sqlite" => Ok(Backend::Sqlite),
+            "git" => Ok(Backend::Git),
+            _ => Err(format!(
+                "invalid backend `{}` (expected json, jsonl, sqlite or git)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Backend::Json => "json",
+            Backend::Jsonl => "jsonl",
+            Backend::Sqlite => "sqlite",
+            Backend::Git => "git",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A journal file, together with the backend used to read and write it.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    pub path: PathBuf,
+    pub backend: Backend,
+}
+
+impl Journal {
+    pub fn new(path: PathBuf, backend: Backend) -> Self {
+        Journal { path, backend }
+    }
+
+    pub fn open(&self) -> io::Result<Box<dyn Storage>> {
+        Ok(match self.backend {
+            Backend::Json => Box::new(JsonStorage::new(self.path.clone())),
+            Backend::Jsonl => Box::new(JsonLinesStorage::new(self.path.clone())),
+            Backend::Sqlite => Box::new(SqliteStorage::open(&self.path)?),
+            Backend::Git => Box::new(KeyedJsonStorage::new(self.path.clone())),
+        })
+    }
+}
+
+/// Write `contents` to a temporary file which is then renamed over `path`, so
+/// that a crash mid-write leaves either the old or the new file, never a mix.
+///
+/// The temporary file is removed if the write fails (e.g. when the disk is full).
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp_path = sibling_path(path, ".tmp");
+
+    let write = || -> io::Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?; // make sure the data is on disk before it replaces `path`
+
+        fs::rename(&tmp_path, path)
+    };
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&tmp_path);
+    })
+}
+
+/// Returns the path of a file next to `path`, with `suffix` added to its name.
+pub fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path
+        .file_name()
+        .unwrap_or_else(|| OsStr::new(""))
+        .to_owned();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::Priority;
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    fn task(id: u64, text: &str) -> Task {
+        let mut task = Task::new(text.to_owned());
+        task.id = id;
+        task.created_at = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        task
+    }
+
+    #[test]
+    fn every_backend_round_trips() {
+        let mut rent = task(2, "Pay rent");
+        rent.priority = Some(Priority::High);
+        rent.due = NaiveDate::from_ymd_opt(2026, 11, 1);
+        rent.tags.insert("home".to_owned());
+        let mut done = task(3, "Water the plants");
+        done.completed_at = Some(Utc.with_ymd_and_hms(2026, 10, 17, 8, 30, 0).unwrap());
+        done.parent = Some(2);
+        done.blocked_by.insert(1);
+        let contents = Contents {
+            tasks: vec![task(1, "Call mom"), rent, done],
+            next_id: 5,
+        };
+
+        for backend in [Backend::Json, Backend::Jsonl, Backend::Sqlite, Backend::Git] {
+            let dir = tempfile::tempdir().unwrap();
+            let journal = Journal::new(dir.path().join("journal"), backend);
+            assert_eq!(journal.open().unwrap().load().unwrap(), Contents::default());
+            // Reading a journal doesn't create it.
+            assert!(!journal.path.exists(), "{}", backend);
+
+            journal.open().unwrap().save(&contents).unwrap();
+            assert_eq!(
+                journal.open().unwrap().load().unwrap(),
+                contents,
+                "{}",
+                backend
+            );
+
+            // Tasks are changed and removed, as well as added.
+            let mut changed = contents.clone();
+            changed.tasks.remove(0);
+            changed.tasks[0].text = "Pay the rent".to_owned();
+            changed.next_id = 6;
+            let mut storage = journal.open().unwrap();
+            storage.save(&changed).unwrap();
+            assert_eq!(storage.load().unwrap(), changed, "{}", backend);
+            assert_eq!(
+                journal.open().unwrap().load().unwrap(),
+                changed,
+                "{}",
+                backend
+            );
+        }
+    }
+
+    #[test]
+    fn atomic_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.json");
+        write_atomically(&path, b"[]").unwrap();
+        write_atomically(&path, b"[{}]").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"[{}]");
+        assert!(!sibling_path(&path, ".tmp").exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn failed_writes_keep_the_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.json");
+        write_atomically(&path, b"[]").unwrap();
+
+        // @Note: writing to `/dev/full` always fails, as if the disk was full.
+        let tmp_path = sibling_path(&path, ".tmp");
+        std::os::unix::fs::symlink("/dev/full", &tmp_path).unwrap();
+
+        let e = write_atomically(&path, b"[{}]").unwrap_err();
+        assert_eq!(e.raw_os_error(), Some(28)); // ENOSPC
+        assert_eq!(fs::read(&path).unwrap(), b"[]");
+        assert!(fs::symlink_metadata(&tmp_path).is_err());
+    }
+}
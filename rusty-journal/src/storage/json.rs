@@ -0,0 +1,59 @@
+use super::{write_atomically, Contents, Storage};
+use crate::tasks::Task;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{self, ErrorKind, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// Stores the whole journal as a single JSON object, e.g.:
+///
+/// ```json
+/// {"tasks":[{"id":1,"text":"Water the plants","created_at":1792108800}],"next_id":3}
+/// ```
+pub struct JsonStorage {
+    path: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(path: PathBuf) -> Self {
+        JsonStorage { path }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonJournal {
+    Current(Contents),
+    /// A plain array of tasks, as journals were written before they kept `next_id`.
+    Legacy(Vec<Task>),
+}
+
+/// Parse the given `file` into the contents of a journal.
+fn collect_tasks(mut file: &File) -> io::Result<Contents> {
+    let initial_offset = file.stream_position()?;
+
+    file.seek(SeekFrom::Start(0))?; // rewind the file before reading from it
+    let tasks = serde_json::from_reader(file);
+    file.seek(SeekFrom::Start(initial_offset))?; // restore the cursor offset
+
+    match tasks {
+        Ok(JsonJournal::Current(contents)) => Ok(contents),
+        Ok(JsonJournal::Legacy(tasks)) => Ok(Contents { tasks, next_id: 0 }),
+        Err(e) if e.is_eof() => Ok(Contents::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load(&self) -> io::Result<Contents> {
+        match File::open(&self.path) {
+            Ok(file) => collect_tasks(&file),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Contents::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&mut self, contents: &Contents) -> io::Result<()> {
+        write_atomically(&self.path, &serde_json::to_vec(contents)?)
+    }
+}
@@ -0,0 +1,240 @@
+use super::{write_atomically, Contents, Storage};
+use crate::tasks::Task;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::PathBuf;
+
+/// A change made to the journal, as recorded in the event log.
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Event {
+    /// A task was added or modified.
+    Put(Task),
+    /// A task was removed (e.g. when undoing its addition).
+    Remove { id: u64 },
+    /// The ID to give to the next task added changed.
+    #[serde(rename = "next_id")]
+    NextId { id: u64 },
+}
+
+/// Stores the journal as an append-only log of events, one JSON object per line.
+///
+/// The tasks are rebuilt by replaying every event, and saving only appends the
+/// events needed to go from the current state to the new one, until the log is
+/// compacted (see `COMPACTION_THRESHOLD`).
+pub struct JsonLinesStorage {
+    path: PathBuf,
+}
+
+/// Number of events past which the log is rewritten as a snapshot of the journal
+/// (one event per task), when most of them are history.
+const COMPACTION_THRESHOLD: usize = 1000;
+
+fn write_event(events: &mut Vec<u8>, event: &Event) -> io::Result<()> {
+    serde_json::to_writer(&mut *events, event)?;
+    events.push(b'\n');
+    Ok(())
+}
+
+impl JsonLinesStorage {
+    pub fn new(path: PathBuf) -> Self {
+        JsonLinesStorage { path }
+    }
+
+    /// Replays the log, returning the journal and the number of events in it.
+    fn replay(&self) -> io::Result<(Contents, usize)> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok((Contents::default(), 0)),
+            Err(e) => return Err(e),
+        };
+
+        let mut tasks = BTreeMap::new();
+        let mut next_id = 0;
+        let lines: Vec<&str> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+
+        for (index, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(Event::Put(task)) => {
+                    tasks.insert(task.id, task);
+                }
+                Ok(Event::Remove { id }) => {
+                    tasks.remove(&id);
+                }
+                Ok(Event::NextId { id }) => next_id = id,
+                // @Note: a crash in the middle of an append can leave a truncated
+                // last line behind, which is ignored (the write never completed).
+                Err(_) if index + 1 == lines.len() && !content.ends_with('\n') => break,
+                Err(e) => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid event on line #{}: {}", index + 1, e),
+                    ))
+                }
+            }
+        }
+
+        let contents = Contents {
+            tasks: tasks.into_values().collect(),
+            next_id,
+        };
+        Ok((contents, lines.len()))
+    }
+
+    /// Replaces the log by the events adding each task of `contents`.
+    fn compact(&self, contents: &Contents) -> io::Result<()> {
+        let mut events = Vec::new();
+        for task in &contents.tasks {
+            write_event(&mut events, &Event::Put(task.clone()))?;
+        }
+        let id = contents.next_id;
+        write_event(&mut events, &Event::NextId { id })?;
+        write_atomically(&self.path, &events)
+    }
+}
+
+impl Storage for JsonLinesStorage {
+    fn load(&self) -> io::Result<Contents> {
+        Ok(self.replay()?.0)
+    }
+
+    fn save(&mut self, contents: &Contents) -> io::Result<()> {
+        let (old_contents, old_events) = self.replay()?;
+        let old_tasks: BTreeMap<u64, Task> = old_contents
+            .tasks
+            .into_iter()
+            .map(|task| (task.id, task))
+            .collect();
+        let tasks = &contents.tasks;
+
+        let mut events = Vec::new();
+        let mut new_events = 0;
+        for task in tasks {
+            if old_tasks.get(&task.id) != Some(task) {
+                write_event(&mut events, &Event::Put(task.clone()))?;
+                new_events += 1;
+            }
+        }
+        for &id in old_tasks.keys() {
+            if !tasks.iter().any(|task| task.id == id) {
+                write_event(&mut events, &Event::Remove { id })?;
+                new_events += 1;
+            }
+        }
+        if contents.next_id != old_contents.next_id {
+            let id = contents.next_id;
+            write_event(&mut events, &Event::NextId { id })?;
+            new_events += 1;
+        }
+
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        // @Note: the log is only compacted when it's at least twice as long as its
+        // snapshot, so that it isn't rewritten on every save of a big journal.
+        let total_events = old_events + new_events;
+        if total_events > COMPACTION_THRESHOLD && total_events > 2 * (tasks.len() + 1) {
+            return self.compact(contents);
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        // @Note: a last line without a line ending would get the first appended
+        // event glued to it, so it's either ended, when it's a whole event, or
+        // dropped, when it's a truncated one (which `load` ignores anyway).
+        let content = fs::read(&self.path)?;
+        let complete_len = content
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        if complete_len < content.len() {
+            let last_line = &content[complete_len..];
+            if serde_json::from_slice::<Event>(last_line).is_ok() {
+                file.write_all(b"\n")?;
+            } else {
+                file.set_len(complete_len as u64)?;
+            }
+        }
+
+        file.write_all(&events)?;
+        file.sync_all()
+    }
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn task(id: u64, text: &str) -> Task {
+        let mut task = Task::new(text.to_owned());
+        task.id = id;
+        task.created_at = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        task
+    }
+
+    #[test]
+    fn truncated_last_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+        let mut storage = JsonLinesStorage::new(path.clone());
+
+        let mut contents = Contents {
+            tasks: vec![task(1, "Water the plants")],
+            next_id: 2,
+        };
+        storage.save(&contents).unwrap();
+
+        // As if a crash happened in the middle of an append.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"op":"put","id":2,"te"#).unwrap();
+        assert_eq!(storage.load().unwrap(), contents);
+
+        contents.tasks.push(task(2, "Pay rent"));
+        contents.next_id = 3;
+        storage.save(&contents).unwrap();
+        assert_eq!(storage.load().unwrap(), contents);
+        assert!(fs::read_to_string(&path).unwrap().ends_with("}\n"));
+
+        // A whole last event which is missing its line ending is kept.
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.trim_end()).unwrap();
+        contents.tasks[0].text = "Water the cactus".to_owned();
+        storage.save(&contents).unwrap();
+        assert_eq!(storage.load().unwrap(), contents);
+    }
+
+    #[test]
+    fn compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+        let mut storage = JsonLinesStorage::new(path.clone());
+
+        let mut contents = Contents {
+            tasks: vec![task(1, "Water the plants"), task(2, "Pay rent")],
+            next_id: 3,
+        };
+        storage.save(&contents).unwrap();
+        for i in 0..COMPACTION_THRESHOLD {
+            contents.tasks[1].text = format!("Pay rent #{}", i);
+            storage.save(&contents).unwrap();
+        }
+
+        assert_eq!(storage.load().unwrap(), contents);
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        assert!(lines < 10, "{} events left", lines);
+    }
+}
@@ -0,0 +1,93 @@
+use super::{write_atomically, Contents, Storage};
+use crate::tasks::Task;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+/// Key of the ID given to the next task added, which comes before the tasks.
+const NEXT_ID_KEY: &str = "next_id";
+
+/// Stores the journal as a JSON object keyed by task ID, e.g.:
+///
+/// ```json
+/// {
+/// "next_id": 3,
+/// "1": {"id":1,"text":"Water the plants","created_at":1792108800},
+/// "2": {"id":2,"text":"Pay rent","created_at":1792108860,"priority":"high"}
+/// }
+/// ```
+///
+/// With one task per line, in ID order, a change to a task only touches its own
+/// line, which keeps diffs readable (and merges simple) when syncing through git.
+pub struct KeyedJsonStorage {
+    path: PathBuf,
+}
+
+impl KeyedJsonStorage {
+    pub fn new(path: PathBuf) -> Self {
+        KeyedJsonStorage { path }
+    }
+}
+
+impl Storage for KeyedJsonStorage {
+    fn load(&self) -> io::Result<Contents> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Contents::default()),
+            Err(e) => return Err(e),
+        };
+        if content.trim().is_empty() {
+            return Ok(Contents::default());
+        }
+
+        let mut entries: BTreeMap<String, Value> = serde_json::from_str(&content)?;
+        let next_id = match entries.remove(NEXT_ID_KEY) {
+            Some(next_id) => serde_json::from_value(next_id)?,
+            None => 0,
+        };
+
+        // @Note: the key is what identifies a task (e.g. after a hand-resolved merge),
+        // so it takes precedence over the ID stored in the task itself.
+        let mut tasks = Vec::with_capacity(entries.len());
+        for (key, task) in entries {
+            let id = key.parse().map_err(|_| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid task id `{}` (expected a number)", key),
+                )
+            })?;
+            let mut task: Task = serde_json::from_value(task)?;
+            task.id = id;
+            tasks.push(task);
+        }
+        tasks.sort_by_key(|task| task.id);
+
+        Ok(Contents { tasks, next_id })
+    }
+
+    fn save(&mut self, contents: &Contents) -> io::Result<()> {
+        let mut by_id: Vec<&Task> = contents.tasks.iter().collect();
+        by_id.sort_by_key(|task| task.id);
+
+        let mut content = String::from("{\n");
+        let separator = if by_id.is_empty() { "" } else { "," };
+        content.push_str(&format!(
+            "\"{}\": {}{}\n",
+            NEXT_ID_KEY, contents.next_id, separator
+        ));
+        for (i, task) in by_id.iter().enumerate() {
+            let separator = if i + 1 < by_id.len() { "," } else { "" };
+            content.push_str(&format!(
+                "\"{}\": {}{}\n",
+                task.id,
+                serde_json::to_string(task)?,
+                separator
+            ));
+        }
+        content.push_str("}\n");
+
+        write_atomically(&self.path, content.as_bytes())
+    }
+}
@@ -0,0 +1,114 @@
+use super::{Contents, Storage};
+use crate::tasks::Task;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Stores the journal in a SQLite database file, with one row per task.
+///
+/// @Note: besides the ID, each task is kept as a JSON document, so that new
+/// task fields don't require a schema migration.
+pub struct SqliteStorage {
+    path: PathBuf,
+    /// Only opened once the database exists, so that reading a journal which
+    /// doesn't exist yet doesn't create it.
+    connection: Option<Connection>,
+}
+
+fn to_io_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Opens (or creates) the database at `path`, with its tables.
+fn connect(path: &Path) -> io::Result<Connection> {
+    let connection = Connection::open(path).map_err(to_io_error)?;
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS tasks (id INTEGER PRIMARY KEY, task TEXT NOT NULL)",
+            [],
+        )
+        .map_err(to_io_error)?;
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS counters (name TEXT PRIMARY KEY, value INTEGER NOT NULL)",
+            [],
+        )
+        .map_err(to_io_error)?;
+    Ok(connection)
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let connection = if path.exists() {
+            Some(connect(path)?)
+        } else {
+            None
+        };
+        Ok(SqliteStorage {
+            path: path.to_owned(),
+            connection,
+        })
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&self) -> io::Result<Contents> {
+        let Some(connection) = &self.connection else {
+            return Ok(Contents::default());
+        };
+        let mut statement = connection
+            .prepare("SELECT task FROM tasks ORDER BY id")
+            .map_err(to_io_error)?;
+
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(to_io_error)?;
+
+        let tasks = rows
+            .map(|row| Ok(serde_json::from_str(&row.map_err(to_io_error)?)?))
+            .collect::<io::Result<Vec<Task>>>()?;
+
+        let next_id = connection
+            .query_row(
+                "SELECT value FROM counters WHERE name = 'next_id'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()
+            .map_err(to_io_error)?
+            .unwrap_or(0);
+
+        Ok(Contents {
+            tasks,
+            next_id: next_id as u64,
+        })
+    }
+
+    fn save(&mut self, contents: &Contents) -> io::Result<()> {
+        // @Note: everything happens inside a single transaction, so either all
+        // of the changes make it into the database or none of them do.
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => self.connection.insert(connect(&self.path)?),
+        };
+        let transaction = connection.transaction().map_err(to_io_error)?;
+        transaction
+            .execute("DELETE FROM tasks", [])
+            .map_err(to_io_error)?;
+        for task in &contents.tasks {
+            transaction
+                .execute(
+                    "INSERT INTO tasks (id, task) VALUES (?1, ?2)",
+                    params![task.id as i64, serde_json::to_string(task)?],
+                )
+                .map_err(to_io_error)?;
+        }
+        transaction
+            .execute(
+                "INSERT OR REPLACE INTO counters (name, value) VALUES ('next_id', ?1)",
+                params![contents.next_id as i64],
+            )
+            .map_err(to_io_error)?;
+        transaction.commit().map_err(to_io_error)
+    }
+}
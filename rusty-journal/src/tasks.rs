@@ -1,112 +1,834 @@
-use chrono::{serde::ts_seconds, DateTime, Local, Utc};
+use crate::recurrence::Recurrence;
+use crate::tracking::WorkInterval;
+use chrono::{
+    serde::{ts_seconds, ts_seconds_option},
+    DateTime, Duration, Local, NaiveDate, Utc,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" | "l" => Ok(Priority::Low),
+            "medium" | "med" | "m" => Ok(Priority::Medium),
+            "high" | "h" => Ok(Priority::High),
+            _ => Err(format!(
+                "invalid priority `{}` (expected low, medium or high)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Task {
+    // @Note: journals written before tasks had IDs are missing this field,
+    // so it defaults to 0 and a fresh ID is assigned when they're loaded.
+    #[serde(default)]
+    pub id: u64,
+
     pub text: String,
 
     #[serde(with = "ts_seconds")]
     pub created_at: DateTime<Utc>,
+
+    #[serde(
+        default,
+        with = "ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub completed_at: Option<DateTime<Utc>>,
+
+    // @Note: the fields below were added later on, so they're optional in order
+    // to keep reading journals which were written before they existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub intervals: Vec<WorkInterval>,
+
+    /// The task this is a subtask of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u64>,
+
+    /// Tasks which must be done before this one.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub blocked_by: BTreeSet<u64>,
+
+    /// Whether the (completed) task was moved to the archive, which keeps it out
+    /// of the way of `list` while still counting towards `stats`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
 }
 
 impl Task {
     pub fn new(text: String) -> Self {
         Task {
+            id: 0,
             text,
             created_at: Utc::now(),
+            completed_at: None,
+            priority: None,
+            due: None,
+            tags: BTreeSet::new(),
+            recurrence: None,
+            intervals: Vec::new(),
+            parent: None,
+            blocked_by: BTreeSet::new(),
+            archived: false,
         }
     }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    /// Whether time is currently being tracked on the task.
+    pub fn is_running(&self) -> bool {
+        self.intervals.iter().any(WorkInterval::is_running)
+    }
+
+    /// Stop tracking time on the task (if it was being tracked).
+    fn stop_tracking(&mut self, now: DateTime<Utc>) {
+        for interval in self.intervals.iter_mut().filter(|i| i.is_running()) {
+            interval.end = Some(now);
+        }
+    }
+
+    /// Make the task repeat, being due on its first occurrence counting from today
+    /// (unless it already has a due date), and anchor `recurrence` to that date.
+    pub fn set_recurrence(&mut self, recurrence: Recurrence) {
+        let first_due = self.due.unwrap_or_else(|| {
+            let today = Local::now().date_naive();
+            let yesterday = today - Duration::days(1);
+            recurrence.clone().anchored(today).next_after(yesterday)
+        });
+
+        self.due = Some(first_due);
+        self.recurrence = Some(recurrence.anchored(first_due));
+    }
+
+    /// Whether the task is still open past its due date.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_completed() && self.due.is_some_and(|due| due < today)
+    }
 }
 
 impl fmt::Display for Task {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let created_at = self.created_at.with_timezone(&Local).format("%F %H:%M");
-        write!(f, "{:<50} [{}]", self.text, created_at)
+        write!(f, "{:<50} [{}]", self.text, created_at)?;
+        if let Some(completed_at) = self.completed_at {
+            let completed_at = completed_at.with_timezone(&Local).format("%F %H:%M");
+            write!(f, " (done {})", completed_at)?;
+        } else if let Some(due) = self.due {
+            write!(f, " (due {})", due.format("%F"))?;
+        }
+        if let Some(priority) = self.priority {
+            write!(f, " !{}", priority)?;
+        }
+        for tag in &self.tags {
+            write!(f, " +{}", tag)?;
+        }
+        if let Some(recurrence) = &self.recurrence {
+            write!(f, " (repeats {})", recurrence)?;
+        }
+        Ok(())
     }
 }
 
-use std::fs::{File, OpenOptions};
-use std::io::{self, ErrorKind, Seek, SeekFrom};
-use std::path::PathBuf;
+use crate::dependencies::{self, OpenDependencies, TreeEntry};
+use crate::merge::{self, Merge};
+use crate::search::{self, MatchMode, SearchResult};
+use crate::storage::{sibling_path, write_atomically, Contents, Journal};
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
 
-/// Parse the given `file` into a vector of tasks.
-fn collect_tasks(mut file: &File) -> io::Result<Vec<Task>> {
-    let initial_offset = file.seek(SeekFrom::Current(0))?;
+/// Move the next ID past the ID of every task, and give an ID to every task that
+/// doesn't have one yet (i.e. from older journals).
+fn assign_ids(contents: &mut Contents) {
+    let max_id = contents.tasks.iter().map(|task| task.id).max().unwrap_or(0);
+    contents.next_id = contents.next_id.max(max_id + 1);
 
-    file.seek(SeekFrom::Start(0))?; // rewind the file before reading from it
-    let tasks = serde_json::from_reader(file);
-    file.seek(SeekFrom::Start(initial_offset))?; // restore the cursor offset
+    for task in contents.tasks.iter_mut().filter(|task| task.id == 0) {
+        task.id = contents.next_id;
+        contents.next_id += 1;
+    }
+}
+
+/// Read the contents of the journal, using whichever backend it is stored with.
+fn load_contents(journal: &Journal) -> io::Result<Contents> {
+    let mut contents = journal.open()?.load()?;
+    assign_ids(&mut contents);
+
+    Ok(contents)
+}
+
+/// Read the tasks in the journal, using whichever backend it is stored with.
+pub fn load_tasks(journal: &Journal) -> io::Result<Vec<Task>> {
+    Ok(load_contents(journal)?.tasks)
+}
 
-    tasks.or_else(|e| {
-        if e.is_eof() {
-            Ok(Vec::new())
-        } else {
-            Err(e.into())
+/// Path of the snapshot used by `undo`, which sits right next to the journal.
+fn undo_path(journal_path: &Path) -> PathBuf {
+    sibling_path(journal_path, ".undo")
+}
+
+/// An advisory lock on the journal, which is released when dropped.
+///
+/// @Note: the lock is taken on a separate `.lock` file, since the journal file
+/// itself gets replaced (and so, a lock on it lost) on every write.
+pub struct JournalLock {
+    _file: File,
+}
+
+impl JournalLock {
+    pub fn acquire(journal_path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(sibling_path(journal_path, ".lock"))?;
+
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(JournalLock { _file: file }),
+            Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+                Err(io::Error::new(
+                    ErrorKind::WouldBlock,
+                    format!(
+                        "The journal file `{}` is locked by another process, try again later",
+                        journal_path.display()
+                    ),
+                ))
+            }
+            Err(e) => Err(e),
         }
+    }
+}
+
+/// Apply `change` to the tasks in the journal, keeping a snapshot of the
+/// previous state around so that it can be restored with `undo`.
+///
+/// `change` gets the ID to give to the next task it adds (and the ones after it
+/// to the following tasks).
+///
+/// The journal stays locked from the moment it's read until the changes are
+/// written back, so concurrent invocations can't overwrite each other.
+fn modify_tasks<T>(
+    journal: &Journal,
+    change: impl FnOnce(&mut Vec<Task>, u64) -> io::Result<T>,
+) -> io::Result<T> {
+    let _lock = JournalLock::acquire(&journal.path)?;
+
+    let mut storage = journal.open()?;
+    let mut contents = storage.load()?;
+    assign_ids(&mut contents);
+    let snapshot = serde_json::to_vec(&contents.tasks)?;
+
+    let result = change(&mut contents.tasks, contents.next_id)?;
+    assign_ids(&mut contents);
+
+    write_atomically(&undo_path(&journal.path), &snapshot)?;
+    storage.save(&contents)?;
+
+    Ok(result)
+}
+
+/// Returns a mutable reference to the task with the given `id`.
+fn find_task(tasks: &mut [Task], id: u64) -> io::Result<&mut Task> {
+    tasks.iter_mut().find(|task| task.id == id).ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid task id: {} (no such task in the journal)", id),
+        )
     })
 }
 
 /// https://docs.microsoft.com/en-us/learn/modules/rust-create-command-line-program/5-add-task-function
-pub fn add_task(journal_path: PathBuf, task: Task) -> io::Result<()> {
-    let file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .open(journal_path)?;
+///
+/// Returns the added task, with the ID it was given.
+pub fn add_task(journal: &Journal, mut task: Task) -> io::Result<Task> {
+    modify_tasks(journal, |tasks, next_id| {
+        task.id = next_id;
+        tasks.push(task.clone());
+        dependencies::check_links(tasks, task.id)?;
 
-    let mut tasks: Vec<Task> = collect_tasks(&file)?;
-    tasks.push(task);
-    serde_json::to_writer(file, &tasks)?;
+        Ok(task)
+    })
+}
 
-    Ok(())
+/// Add every task in `imported` to the journal, giving each of them a new ID.
+pub fn import_tasks(journal: &Journal, imported: Vec<Task>) -> io::Result<usize> {
+    modify_tasks(journal, |tasks, next_id| {
+        let count = imported.len();
+        for (mut task, id) in imported.into_iter().zip(next_id..) {
+            task.id = id;
+            tasks.push(task);
+        }
+
+        Ok(count)
+    })
+}
+
+/// The outcome of `complete_task`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Completion {
+    pub task: Task,
+    /// The next occurrence of the task, when it's a recurring one.
+    pub next: Option<Task>,
+    /// What was still open when the task got completed (if forced to).
+    #[serde(skip_serializing_if = "OpenDependencies::is_empty")]
+    pub open_dependencies: OpenDependencies,
 }
 
 /// https://docs.microsoft.com/en-us/learn/modules/rust-create-command-line-program/6-complete-task-function
-pub fn complete_task(journal_path: PathBuf, task_position: usize) -> io::Result<()> {
-    let file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(journal_path)?;
+///
+/// Returns the completed task and, when it is a recurring one, its next occurrence
+/// (which gets added to the journal).
+///
+/// Tasks with open subtasks or blockers are only completed when `force` is set.
+pub fn complete_task(journal: &Journal, task_id: u64, force: bool) -> io::Result<Completion> {
+    modify_tasks(journal, |tasks, next_id| {
+        let open_dependencies = match tasks.iter().find(|task| task.id == task_id) {
+            Some(task) => OpenDependencies::of(task, tasks),
+            None => OpenDependencies::default(),
+        };
+        let task = find_task(tasks, task_id)?;
+
+        // @Note: completed tasks are kept in the journal (instead of being removed),
+        // so that their history survives and the change can be reverted with `undo`.
+        if task.is_completed() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Task {} is already completed", task_id),
+            ));
+        }
+        if !force && !open_dependencies.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Task {} still has {} (use `--force` to complete it anyway)",
+                    task_id, open_dependencies
+                ),
+            ));
+        }
 
-    let mut tasks = collect_tasks(&file)?;
-    let tasks_len = tasks.len();
+        let now = Utc::now();
+        task.completed_at = Some(now);
+        task.stop_tracking(now);
 
-    // Note: the task position follows 1-based indexing.
-    if (1..=tasks_len).contains(&task_position) {
-        tasks.remove(task_position - 1);
+        let next_task = task.recurrence.as_ref().map(|recurrence| {
+            // @Note: the next occurrence is always scheduled in the future, even
+            // when the task gets done late (i.e. missed occurrences are skipped).
+            let today = Local::now().date_naive();
+            let due = task.due.map_or(today, |due| due.max(today));
 
-        file.set_len(0)?;
-        serde_json::to_writer(file, &tasks)?;
+            let mut next_task = Task::new(task.text.clone());
+            next_task.id = next_id;
+            next_task.priority = task.priority;
+            next_task.tags = task.tags.clone();
+            next_task.parent = task.parent;
+            next_task.due = Some(recurrence.next_after(due));
+            next_task.recurrence = Some(recurrence.clone());
+            next_task
+        });
 
-        Ok(())
-    } else {
-        Err(io::Error::new(
-            ErrorKind::InvalidInput,
+        let task = task.clone();
+        if let Some(next_task) = &next_task {
+            tasks.push(next_task.clone());
+        }
+
+        Ok(Completion {
+            task,
+            next: next_task,
+            open_dependencies,
+        })
+    })
+}
+
+/// Start tracking the time spent on the task with the given `task_id`.
+///
+/// @Note: time can only be tracked on a single task at a time.
+pub fn start_task(journal: &Journal, task_id: u64) -> io::Result<Task> {
+    modify_tasks(journal, |tasks, _| {
+        if let Some(running) = tasks.iter().find(|task| task.is_running()) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Task {} is already being tracked (use `stop` first)",
+                    running.id
+                ),
+            ));
+        }
+
+        let task = find_task(tasks, task_id)?;
+        if task.is_completed() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Task {} is already completed", task_id),
+            ));
+        }
+
+        task.intervals.push(WorkInterval {
+            start: Utc::now(),
+            end: None,
+        });
+
+        Ok(task.clone())
+    })
+}
+
+/// Stop tracking time on the task currently being tracked, returning it.
+pub fn stop_task(journal: &Journal) -> io::Result<Task> {
+    modify_tasks(journal, |tasks, _| {
+        let task = tasks
+            .iter_mut()
+            .find(|task| task.is_running())
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No task is being tracked"))?;
+
+        task.stop_tracking(Utc::now());
+
+        Ok(task.clone())
+    })
+}
+
+/// Apply `edit` to the task with the given `task_id`.
+///
+/// Fails (leaving the journal untouched) if the edited task would have a parent
+/// or blockers which don't exist, or would end up waiting on itself.
+pub fn edit_task(
+    journal: &Journal,
+    task_id: u64,
+    edit: impl FnOnce(&mut Task),
+) -> io::Result<Task> {
+    modify_tasks(journal, |tasks, _| {
+        let task = find_task(tasks, task_id)?;
+        edit(task);
+        let task = task.clone();
+        dependencies::check_links(tasks, task_id)?;
+
+        Ok(task)
+    })
+}
+
+/// Revert the journal to how it was before the last change made to it.
+///
+/// @Note: the next ID isn't reverted, so that tasks added after an undone one
+/// don't get its ID (which may still be referred to somewhere).
+pub fn undo(journal: &Journal) -> io::Result<()> {
+    let _lock = JournalLock::acquire(&journal.path)?;
+    let undo_path = undo_path(&journal.path);
+
+    let snapshot = match fs::read(&undo_path) {
+        Ok(snapshot) => snapshot,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(io::Error::new(ErrorKind::NotFound, "Nothing to undo"));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let tasks: Vec<Task> = serde_json::from_slice(&snapshot)?;
+    let mut storage = journal.open()?;
+    let mut contents = Contents {
+        tasks,
+        next_id: storage.load()?.next_id,
+    };
+    assign_ids(&mut contents);
+    storage.save(&contents)?;
+    fs::remove_file(undo_path)?;
+
+    Ok(())
+}
+
+/// Move completed tasks to the archive: the ones in `ids` or, if none are given,
+/// every task completed before the day `before` (or just every one, by default).
+///
+/// Returns the IDs of the archived tasks.
+pub fn archive_tasks(
+    journal: &Journal,
+    ids: &[u64],
+    before: Option<NaiveDate>,
+) -> io::Result<Vec<u64>> {
+    modify_tasks(journal, |tasks, _| {
+        for &id in ids {
+            let task = find_task(tasks, id)?;
+            if !task.is_completed() {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Task {} can't be archived before it's completed", id),
+                ));
+            }
+        }
+
+        let is_selected = |task: &Task| match task.completed_at {
+            _ if task.archived => false,
+            Some(_) if !ids.is_empty() => ids.contains(&task.id),
+            Some(completed_at) => {
+                let completed_on = completed_at.with_timezone(&Local).date_naive();
+                before.is_none_or(|before| completed_on < before)
+            }
+            None => false,
+        };
+
+        let mut archived = Vec::new();
+        for task in tasks.iter_mut().filter(|task| is_selected(task)) {
+            task.archived = true;
+            archived.push(task.id);
+        }
+
+        Ok(archived)
+    })
+}
+
+/// Move the tasks in `ids` out of the archive, returning how many there were.
+pub fn unarchive_tasks(journal: &Journal, ids: &[u64]) -> io::Result<usize> {
+    modify_tasks(journal, |tasks, _| {
+        for &id in ids {
+            let task = find_task(tasks, id)?;
+            if !task.archived {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Task {} isn't archived", id),
+                ));
+            }
+            task.archived = false;
+        }
+
+        Ok(ids.len())
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    Id,
+    Due,
+    Priority,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "id" => Ok(SortKey::Id),
+            "due" => Ok(SortKey::Due),
+            "priority" => Ok(SortKey::Priority),
+            _ => Err(format!(
+                "invalid sort key `{}` (expected id, due or priority)",
+                s
+            )),
+        }
+    }
+}
+
+/// Which tasks `list_tasks` should show, and in what order.
+#[derive(Debug, Default)]
+pub struct ListOptions {
+    pub show_completed: bool,
+    /// Show the archived tasks (and only them) instead.
+    pub archived: bool,
+    pub tag: Option<String>,
+    pub priority: Option<Priority>,
+    pub overdue: bool,
+    pub sort: Option<SortKey>,
+}
+
+impl ListOptions {
+    fn matches(&self, task: &Task, today: NaiveDate) -> bool {
+        task.archived == self.archived
+            && (self.show_completed || self.archived || !task.is_completed())
+            && self.tag.as_ref().is_none_or(|tag| task.tags.contains(tag))
+            && self
+                .priority
+                .is_none_or(|priority| task.priority == Some(priority))
+            && (!self.overdue || task.is_overdue(today))
+    }
+
+    fn sort(&self, tasks: &mut [Task]) {
+        // @Note: tasks without a due date or priority are always listed last.
+        match self.sort {
+            None | Some(SortKey::Id) => tasks.sort_by_key(|task| task.id),
+            Some(SortKey::Due) => tasks.sort_by_key(|task| (task.due.is_none(), task.due, task.id)),
+            Some(SortKey::Priority) => {
+                tasks.sort_by_key(|task| (std::cmp::Reverse(task.priority), task.id))
+            }
+        }
+    }
+}
+
+/// Three-way merge of the journals `ours` and `theirs` against their common
+/// ancestor `base`, where the result replaces `ours` (as `git merge` expects
+/// from a merge driver). Conflicting changes keep our side.
+///
+/// @Note: `ours` is usually a temporary file made by git, so (unlike other
+/// changes) no lock or undo snapshot is kept next to it.
+pub fn merge_journals(base: &Journal, ours: &Journal, theirs: &Journal) -> io::Result<Merge> {
+    let mut storage = ours.open()?;
+    let (base, ours, theirs) = (
+        load_contents(base)?,
+        load_contents(ours)?,
+        load_contents(theirs)?,
+    );
+    let next_id = base.next_id.max(ours.next_id).max(theirs.next_id);
+    let merged = merge::merge(base.tasks, ours.tasks, theirs.tasks, next_id)?;
+
+    let mut contents = Contents {
+        tasks: merged.tasks.clone(),
+        next_id,
+    };
+    assign_ids(&mut contents);
+    storage.save(&contents)?;
+
+    Ok(merged)
+}
+
+/// Copy every task in the journal `from` into the (empty) journal `to`, which
+/// may use a different backend.
+pub fn migrate(from: &Journal, to: &Journal) -> io::Result<usize> {
+    let _from_lock = JournalLock::acquire(&from.path)?;
+    let _to_lock = JournalLock::acquire(&to.path)?;
+
+    let contents = load_contents(from)?;
+
+    let mut storage = to.open()?;
+    if !storage.load()?.tasks.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
             format!(
-                "Invalid task position: {} (the journal has {} {})",
-                task_position,
-                tasks_len,
-                if tasks_len == 1 { "task" } else { "tasks" }
+                "The journal file `{}` already has tasks in it",
+                to.path.display()
             ),
-        ))
+        ));
     }
+    storage.save(&contents)?;
+
+    Ok(contents.tasks.len())
 }
 
 /// https://docs.microsoft.com/en-us/learn/modules/rust-create-command-line-program/7-list-tasks-function
-pub fn list_tasks(journal_path: PathBuf) -> io::Result<()> {
-    let file = OpenOptions::new().read(true).open(journal_path)?;
+///
+/// Returns the tasks selected by `options`, in the order they should be shown.
+pub fn list_tasks(journal: &Journal, options: &ListOptions) -> io::Result<Vec<Task>> {
+    let today = Local::now().date_naive();
+
+    let mut tasks: Vec<Task> = load_tasks(journal)?
+        .into_iter()
+        .filter(|task| options.matches(task, today))
+        .collect();
+    options.sort(&mut tasks);
+
+    Ok(tasks)
+}
+
+/// Same as `list_tasks`, arranging the tasks in a tree of subtasks.
+pub fn list_tree(journal: &Journal, options: &ListOptions) -> io::Result<Vec<TreeEntry>> {
+    let today = Local::now().date_naive();
+
+    let all_tasks = load_tasks(journal)?;
+    let mut tasks: Vec<Task> = all_tasks
+        .iter()
+        .filter(|task| options.matches(task, today))
+        .cloned()
+        .collect();
+    options.sort(&mut tasks);
+
+    Ok(dependencies::tree(tasks, &all_tasks))
+}
+
+/// Returns the tasks in the journal matching `query`, best matches first.
+///
+/// Fails if `query` is blank, since it would match nothing.
+pub fn search_tasks(
+    journal: &Journal,
+    query: &str,
+    mode: MatchMode,
+    show_completed: bool,
+) -> io::Result<Vec<SearchResult>> {
+    if query.trim().is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "The search query is empty",
+        ));
+    }
+
+    let tasks = load_tasks(journal)?
+        .into_iter()
+        .filter(|task| show_completed || !task.is_completed());
+
+    Ok(search::search(tasks, query, mode))
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Backend;
+
+    fn journal(dir: &tempfile::TempDir) -> Journal {
+        Journal::new(dir.path().join("journal.json"), Backend::Json)
+    }
+
+    fn add(journal: &Journal, text: &str) -> Task {
+        add_task(journal, Task::new(text.to_owned())).unwrap()
+    }
 
-    let tasks = collect_tasks(&file)?;
+    #[test]
+    fn edit_and_complete() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(&dir);
+        add(&journal, "Water the plants");
+        let task = add(&journal, "Pay rent");
+        assert_eq!(task.id, 2);
 
-    if tasks.is_empty() {
-        println!("The task list is empty");
-    } else {
-        for (position, task) in tasks.iter().enumerate() {
-            // Note: the task position follows 1-based indexing.
-            println!("{}: {}", position + 1, task);
+        let edited = edit_task(&journal, 2, |task| task.text = "Pay the rent".to_owned()).unwrap();
+        assert_eq!(edited.text, "Pay the rent");
+        assert!(edit_task(&journal, 3, |_| {}).is_err());
+
+        let completion = complete_task(&journal, 2, false).unwrap();
+        assert!(completion.task.completed_at.is_some());
+        assert!(completion.next.is_none());
+        assert!(complete_task(&journal, 2, false).is_err());
+
+        let tasks = load_tasks(&journal).unwrap();
+        assert_eq!(tasks[1].text, "Pay the rent");
+        // @Note: the journal keeps timestamps in seconds.
+        let completed_at = completion.task.completed_at.map(|t| t.timestamp());
+        assert_eq!(tasks[1].completed_at.map(|t| t.timestamp()), completed_at);
+        assert!(!tasks[0].is_completed());
+    }
+
+    #[test]
+    fn undo_never_reuses_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(&dir);
+        add(&journal, "Water the plants");
+        add(&journal, "Pay rent");
+
+        undo(&journal).unwrap();
+        let tasks = load_tasks(&journal).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].text, "Water the plants");
+        assert!(undo(&journal).is_err()); // there's a single snapshot
+
+        // The undone task's ID isn't given to the next one.
+        assert_eq!(add(&journal, "Call mom").id, 3);
+    }
+
+    #[test]
+    fn empty_search_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(&dir);
+        add(&journal, "Water the plants");
+
+        for mode in [MatchMode::Substring, MatchMode::Fuzzy] {
+            let e = search_tasks(&journal, " ", mode, false).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidInput);
+            assert_eq!(
+                search_tasks(&journal, "plant", mode, false).unwrap().len(),
+                1
+            );
         }
     }
 
-    Ok(())
+    #[test]
+    fn locked_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(&dir);
+        add(&journal, "Water the plants");
+
+        let lock = JournalLock::acquire(&journal.path).unwrap();
+        let e = add_task(&journal, Task::new("Pay rent".to_owned())).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::WouldBlock);
+        assert!(e.to_string().contains("locked by another process"));
+        assert!(JournalLock::acquire(&journal.path).is_err());
+
+        drop(lock);
+        add(&journal, "Pay rent");
+        assert_eq!(load_tasks(&journal).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn migrate_between_backends() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = journal(&dir);
+        let sqlite = Journal::new(dir.path().join("journal.db"), Backend::Sqlite);
+        let jsonl = Journal::new(dir.path().join("journal.jsonl"), Backend::Jsonl);
+
+        add(&json, "Water the plants");
+        let mut task = Task::new("Pay rent".to_owned());
+        task.priority = Some(Priority::High);
+        task.tags.insert("home".to_owned());
+        task.blocked_by.insert(1);
+        add_task(&json, task).unwrap();
+        add(&json, "Call mom");
+        undo(&json).unwrap();
+        complete_task(&json, 1, false).unwrap();
+        let tasks = load_tasks(&json).unwrap();
+
+        assert_eq!(migrate(&json, &sqlite).unwrap(), 2);
+        assert_eq!(migrate(&sqlite, &jsonl).unwrap(), 2);
+        assert_eq!(load_tasks(&sqlite).unwrap(), tasks);
+        assert_eq!(load_tasks(&jsonl).unwrap(), tasks);
+
+        // The next ID comes along, so that IDs keep never being reused.
+        assert_eq!(add(&jsonl, "Buy milk").id, 4);
+        assert!(migrate(&json, &jsonl).is_err()); // not empty
+    }
+
+    #[test]
+    fn ids_of_legacy_journals() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(&dir);
+        fs::write(
+            &journal.path,
+            r#"[{"text":"Water the plants","created_at":1792108800},{"text":"Pay rent","created_at":1792108860}]"#,
+        )
+        .unwrap();
+
+        let ids: Vec<u64> = load_tasks(&journal).unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(add(&journal, "Call mom").id, 3);
+
+        let ids: Vec<u64> = load_tasks(&journal).unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
 }
@@ -0,0 +1,263 @@
+use crate::tasks::Task;
+use chrono::{
+    serde::{ts_seconds, ts_seconds_option},
+    DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A period of time spent working on a task.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct WorkInterval {
+    #[serde(with = "ts_seconds")]
+    pub start: DateTime<Utc>,
+
+    /// When the work stopped, or `None` if it's still going on.
+    #[serde(
+        default,
+        with = "ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl WorkInterval {
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Returns when the interval ended, where running intervals end at `now`.
+    fn end_or(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.end.unwrap_or(now).max(self.start)
+    }
+}
+
+/// Returns the total time tracked on `task`, counting running intervals up to `now`.
+pub fn tracked_time(task: &Task, now: DateTime<Utc>) -> Duration {
+    task.intervals
+        .iter()
+        .map(|interval| interval.end_or(now) - interval.start)
+        .fold(Duration::zero(), |total, duration| total + duration)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    Day,
+    Week,
+    Tag,
+    Task,
+}
+
+impl FromStr for Grouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(Grouping::Day),
+            "week" => Ok(Grouping::Week),
+            "tag" => Ok(Grouping::Tag),
+            "task" => Ok(Grouping::Task),
+            _ => Err(format!(
+                "invalid grouping `{}` (expected day, week, tag or task)",
+                s
+            )),
+        }
+    }
+}
+
+/// Total time tracked for one group of a report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportRow {
+    pub group: String,
+    pub seconds: i64,
+}
+
+/// Splits the interval `[start, end)` at (local) midnights, returning how much of
+/// it falls on each day.
+fn split_by_day<Tz: TimeZone>(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    timezone: &Tz,
+) -> Vec<(NaiveDate, Duration)> {
+    let mut days = Vec::new();
+    let mut start = start;
+
+    while start < end {
+        let day = start.with_timezone(timezone).date_naive();
+        let next_midnight = day
+            .succ_opt()
+            .and_then(|next_day| next_day.and_hms_opt(0, 0, 0))
+            .and_then(|midnight| timezone.from_local_datetime(&midnight).earliest())
+            .map(|midnight| midnight.with_timezone(&Utc))
+            .unwrap_or(end);
+
+        let day_end = if next_midnight > start {
+            next_midnight.min(end)
+        } else {
+            end
+        };
+        days.push((day, day_end - start));
+        start = day_end;
+    }
+
+    days
+}
+
+/// Sums the time tracked on `tasks` (in the given `timezone`), grouped by `grouping`
+/// and counting only the time between `from` and `to` (inclusive), if given.
+pub fn report_in<Tz: TimeZone>(
+    tasks: &[Task],
+    grouping: Grouping,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    now: DateTime<Utc>,
+    timezone: &Tz,
+) -> Vec<ReportRow> {
+    let mut totals: BTreeMap<String, Duration> = BTreeMap::new();
+
+    for task in tasks {
+        for interval in &task.intervals {
+            for (day, duration) in split_by_day(interval.start, interval.end_or(now), timezone) {
+                if from.is_some_and(|from| day < from) || to.is_some_and(|to| day > to) {
+                    continue;
+                }
+
+                let groups = match grouping {
+                    Grouping::Day => vec![day.format("%F").to_string()],
+                    Grouping::Week => {
+                        let week = day.iso_week();
+                        vec![format!("{}-W{:02}", week.year(), week.week())]
+                    }
+                    // @Note: the time spent on a task counts towards each of its tags.
+                    Grouping::Tag if task.tags.is_empty() => vec!["(untagged)".to_owned()],
+                    Grouping::Tag => task.tags.iter().map(|tag| format!("+{}", tag)).collect(),
+                    Grouping::Task => vec![format!("{}: {}", task.id, task.text)],
+                };
+
+                for group in groups {
+                    let total = totals.entry(group).or_insert_with(Duration::zero);
+                    *total += duration;
+                }
+            }
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|(group, duration)| ReportRow {
+            group,
+            seconds: duration.num_seconds(),
+        })
+        .collect()
+}
+
+/// Same as `report_in`, using the local timezone and the current time.
+pub fn report(
+    tasks: &[Task],
+    grouping: Grouping,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Vec<ReportRow> {
+    report_in(tasks, grouping, from, to, Utc::now(), &Local)
+}
+
+/// Formats a number of seconds as e.g. "2h 05m".
+pub struct HoursMinutes(pub i64);
+
+impl fmt::Display for HoursMinutes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.0 / 60;
+        let text = format!("{}h {:02}m", minutes / 60, minutes % 60);
+        f.pad(&text)
+    }
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn tracked_task(id: u64, tags: &[&str], intervals: Vec<WorkInterval>) -> Task {
+        let mut task = Task::new(format!("task {}", id));
+        task.id = id;
+        task.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        task.intervals = intervals;
+        task
+    }
+
+    fn interval(start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> WorkInterval {
+        WorkInterval { start, end }
+    }
+
+    fn rows(rows: &[(&str, i64)]) -> Vec<ReportRow> {
+        rows.iter()
+            .map(|&(group, minutes)| ReportRow {
+                group: group.to_owned(),
+                seconds: minutes * 60,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn intervals_across_midnight() {
+        let tasks = vec![tracked_task(
+            1,
+            &[],
+            vec![interval(at(16, 23, 30), Some(at(17, 1, 0)))],
+        )];
+        let now = at(20, 0, 0);
+
+        assert_eq!(
+            report_in(&tasks, Grouping::Day, None, None, now, &Utc),
+            rows(&[("2026-10-16", 30), ("2026-10-17", 60)])
+        );
+        assert_eq!(
+            report_in(
+                &tasks,
+                Grouping::Day,
+                NaiveDate::from_ymd_opt(2026, 10, 17),
+                None,
+                now,
+                &Utc
+            ),
+            rows(&[("2026-10-17", 60)])
+        );
+    }
+
+    #[test]
+    fn grouping_by_tag_week_and_task() {
+        let tasks = vec![
+            tracked_task(
+                1,
+                &["work", "rust"],
+                vec![interval(at(12, 9, 0), Some(at(12, 10, 0)))],
+            ),
+            tracked_task(2, &["work"], vec![interval(at(19, 9, 0), None)]),
+            tracked_task(3, &[], vec![interval(at(19, 8, 0), Some(at(19, 8, 15)))]),
+        ];
+        let now = at(19, 9, 30); // task 2 is still running
+
+        assert_eq!(
+            report_in(&tasks, Grouping::Tag, None, None, now, &Utc),
+            rows(&[("(untagged)", 15), ("+rust", 60), ("+work", 90)])
+        );
+        assert_eq!(
+            report_in(&tasks, Grouping::Week, None, None, now, &Utc),
+            rows(&[("2026-W42", 60), ("2026-W43", 45)])
+        );
+        assert_eq!(
+            report_in(&tasks, Grouping::Task, None, None, now, &Utc),
+            rows(&[("1: task 1", 60), ("2: task 2", 30), ("3: task 3", 15)])
+        );
+    }
+}
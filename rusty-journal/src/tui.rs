@@ -0,0 +1,535 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use rusty_journal::dependencies::TreeEntry;
+use rusty_journal::inline;
+use rusty_journal::storage::Journal;
+use rusty_journal::tasks::{self, ListOptions, Priority, Task};
+use std::io;
+
+const HELP: &str = "a add  A add subtask  e edit  d done  D force done  +/- priority  \
+                    / filter  c completed  o overdue  u undo  q quit";
+
+/// What the text typed at the prompt is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Add { parent: Option<u64> },
+    Edit { id: u64 },
+    Filter,
+}
+
+impl Prompt {
+    fn label(&self) -> String {
+        match self {
+            Prompt::Add { parent: None } => {
+                "New task (e.g. Pay rent +home !high due:fri): ".to_owned()
+            }
+            Prompt::Add { parent: Some(id) } => format!("New subtask of {}: ", id),
+            Prompt::Edit { id } => format!("Task {}: ", id),
+            Prompt::Filter => "Filter (text or +tag): ".to_owned(),
+        }
+    }
+}
+
+struct App {
+    journal: Journal,
+    options: ListOptions,
+    /// Only show tasks whose text contains this (ignoring case).
+    query: String,
+    entries: Vec<TreeEntry>,
+    list_state: ListState,
+    input: Option<(Prompt, String)>,
+    status: String,
+    quit: bool,
+}
+
+/// Returns the next higher (or lower) priority, where no priority is the lowest.
+fn shift_priority(priority: Option<Priority>, raise: bool) -> Option<Priority> {
+    use Priority::*;
+    match (priority, raise) {
+        (None, true) => Some(Low),
+        (Some(Low), true) => Some(Medium),
+        (Some(Medium) | Some(High), true) => Some(High),
+        (None | Some(Low), false) => None,
+        (Some(Medium), false) => Some(Low),
+        (Some(High), false) => Some(Medium),
+    }
+}
+
+impl App {
+    fn new(journal: Journal, options: ListOptions) -> Self {
+        let mut app = App {
+            journal,
+            options,
+            query: String::new(),
+            entries: Vec::new(),
+            list_state: ListState::default(),
+            input: None,
+            status: String::new(),
+            quit: false,
+        };
+        app.reload(None);
+        app
+    }
+
+    fn selected(&self) -> Option<&Task> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.entries.get(index))
+            .map(|entry| &entry.task)
+    }
+
+    /// Read the tasks again, keeping the task with `select_id` selected (if any).
+    fn reload(&mut self, select_id: Option<u64>) {
+        let query = self.query.to_lowercase();
+        match tasks::list_tree(&self.journal, &self.options) {
+            Ok(entries) => {
+                self.entries = entries
+                    .into_iter()
+                    .filter(|entry| entry.task.text.to_lowercase().contains(&query))
+                    .collect()
+            }
+            Err(e) => self.status = format!("Error: {}", e),
+        }
+
+        let index = select_id
+            .and_then(|id| self.entries.iter().position(|entry| entry.task.id == id))
+            .or_else(|| self.list_state.selected())
+            .map(|index| index.min(self.entries.len().saturating_sub(1)));
+        self.list_state.select(if self.entries.is_empty() {
+            None
+        } else {
+            index.or(Some(0))
+        });
+    }
+
+    /// Show the outcome of an operation on the journal, and reload it.
+    fn apply(&mut self, result: io::Result<(u64, String)>) {
+        match result {
+            Ok((id, status)) => {
+                self.status = status;
+                self.reload(Some(id));
+            }
+            Err(e) => self.status = format!("Error: {}", e),
+        }
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let last = self.entries.len() - 1;
+        let index = self.list_state.selected().unwrap_or(0);
+        let index = if offset < 0 {
+            index.saturating_sub(offset.unsigned_abs())
+        } else {
+            index.saturating_add(offset as usize).min(last)
+        };
+        self.list_state.select(Some(index));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if let Some((prompt, mut text)) = self.input.take() {
+            match key.code {
+                KeyCode::Enter => self.submit(prompt, text),
+                KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    text.pop();
+                    self.input = Some((prompt, text));
+                }
+                KeyCode::Char(c) => {
+                    text.push(c);
+                    self.input = Some((prompt, text));
+                }
+                _ => self.input = Some((prompt, text)),
+            }
+            return;
+        }
+
+        let selected = self.selected().map(|task| (task.id, task.text.clone()));
+        let selected_id = selected.as_ref().map(|(id, _)| *id);
+        match (key.code, selected) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => self.quit = true,
+            (KeyCode::Down | KeyCode::Char('j'), _) => self.move_selection(1),
+            (KeyCode::Up | KeyCode::Char('k'), _) => self.move_selection(-1),
+            (KeyCode::PageDown, _) => self.move_selection(10),
+            (KeyCode::PageUp, _) => self.move_selection(-10),
+            (KeyCode::Home | KeyCode::Char('g'), _) => self.move_selection(isize::MIN),
+            (KeyCode::End | KeyCode::Char('G'), _) => self.move_selection(isize::MAX),
+            (KeyCode::Char('a'), _) => {
+                self.input = Some((Prompt::Add { parent: None }, String::new()))
+            }
+            (KeyCode::Char('A'), Some((id, _))) => {
+                self.input = Some((Prompt::Add { parent: Some(id) }, String::new()))
+            }
+            (KeyCode::Char('e'), Some((id, text))) => {
+                self.input = Some((Prompt::Edit { id }, text))
+            }
+            (KeyCode::Char('/'), _) => self.input = Some((Prompt::Filter, self.query.clone())),
+            (KeyCode::Char(c @ ('d' | 'D')), Some((id, _))) => {
+                let result = tasks::complete_task(&self.journal, id, c == 'D');
+                self.apply(result.map(|completion| {
+                    let mut status = format!("Completed task {}", id);
+                    if let Some(next) = completion.next {
+                        status.push_str(&format!(", next occurrence is task {}", next.id));
+                    }
+                    (id, status)
+                }));
+            }
+            (KeyCode::Char(c @ ('+' | '-')), Some((id, _))) => {
+                let result = tasks::edit_task(&self.journal, id, |task| {
+                    task.priority = shift_priority(task.priority, c == '+');
+                });
+                self.apply(result.map(|task| {
+                    let priority = task.priority.map(|p| p.to_string());
+                    let status = format!(
+                        "Task {} has priority {}",
+                        id,
+                        priority.as_deref().unwrap_or("none")
+                    );
+                    (id, status)
+                }));
+            }
+            (KeyCode::Char('c'), _) => {
+                self.options.show_completed = !self.options.show_completed;
+                self.reload(selected_id);
+            }
+            (KeyCode::Char('o'), _) => {
+                self.options.overdue = !self.options.overdue;
+                self.reload(selected_id);
+            }
+            (KeyCode::Char('u'), _) => match tasks::undo(&self.journal) {
+                Ok(()) => {
+                    self.status = "Reverted the last change".to_owned();
+                    self.reload(selected_id);
+                }
+                Err(e) => self.status = format!("Error: {}", e),
+            },
+            _ => {}
+        }
+    }
+
+    fn submit(&mut self, prompt: Prompt, text: String) {
+        let text = text.trim().to_owned();
+        match prompt {
+            Prompt::Filter => {
+                // @Note: `+tag` filters by tag (like `list --tag`), anything else
+                // filters by the task's text (one filter replacing the other).
+                match text.strip_prefix('+') {
+                    Some(tag) if !tag.is_empty() => {
+                        self.options.tag = Some(tag.to_owned());
+                        self.query.clear();
+                    }
+                    _ => {
+                        self.options.tag = None;
+                        self.query = text;
+                    }
+                }
+                self.reload(None);
+            }
+            _ if text.is_empty() => {}
+            Prompt::Add { parent } => {
+                let inline = match inline::parse(&text) {
+                    Ok(inline) => inline,
+                    Err(e) => {
+                        self.status = format!("Error: {}", e);
+                        return;
+                    }
+                };
+
+                let mut task = Task::new(inline.text);
+                task.priority = inline.priority;
+                task.due = inline.due;
+                task.tags = inline.tags;
+                task.parent = parent;
+                if let Some(recurrence) = inline.recurrence {
+                    task.set_recurrence(recurrence);
+                }
+                let result = tasks::add_task(&self.journal, task);
+                self.apply(result.map(|task| (task.id, format!("Added task {}", task.id))));
+            }
+            Prompt::Edit { id } => {
+                let result = tasks::edit_task(&self.journal, id, |task| task.text = text);
+                self.apply(result.map(|_| (id, format!("Edited task {}", id))));
+            }
+        }
+    }
+
+    fn title(&self) -> String {
+        let mut title = format!(" {} ", self.journal.path.display());
+        if let Some(tag) = &self.options.tag {
+            title.push_str(&format!("+{} ", tag));
+        }
+        if !self.query.is_empty() {
+            title.push_str(&format!("/{} ", self.query));
+        }
+        if self.options.show_completed {
+            title.push_str("(all) ");
+        }
+        if self.options.overdue {
+            title.push_str("(overdue) ");
+        }
+        title
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [list_area, status_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| ListItem::new(entry_line(entry)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(self.title()))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let status = match &self.input {
+            Some((prompt, text)) => {
+                let label = prompt.label();
+                let cursor_x =
+                    status_area.x + (label.chars().count() + text.chars().count()) as u16;
+                frame.set_cursor_position((
+                    cursor_x.min(status_area.right().saturating_sub(1)),
+                    status_area.y,
+                ));
+                format!("{}{}", label, text)
+            }
+            None if self.status.is_empty() => HELP.to_owned(),
+            None => self.status.clone(),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+    }
+}
+
+fn entry_line(entry: &TreeEntry) -> Line<'static> {
+    let task = &entry.task;
+    let mut line = format!(
+        "{}{:>3} [{}] {}",
+        "  ".repeat(entry.depth),
+        task.id,
+        if task.is_completed() { "x" } else { " " },
+        task.text
+    );
+    if let Some(due) = task.due {
+        line.push_str(&format!(" (due {})", due.format("%F")));
+    }
+    if let Some(priority) = task.priority {
+        line.push_str(&format!(" !{}", priority));
+    }
+    for tag in &task.tags {
+        line.push_str(&format!(" +{}", tag));
+    }
+    if !entry.open_blockers.is_empty() {
+        let blockers: Vec<_> = entry.open_blockers.iter().map(u64::to_string).collect();
+        line.push_str(&format!(" (blocked by {})", blockers.join(", ")));
+    }
+    Line::from(line)
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                // @Note: the status line only shows the outcome of the last action.
+                if app.input.is_none() {
+                    app.status.clear();
+                }
+                app.handle_key(key);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the full-screen interface on `journal`, until the user quits.
+pub fn run(journal: Journal, options: ListOptions) -> io::Result<()> {
+    let mut app = App::new(journal, options);
+
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::try_restore()?;
+
+    result
+}
+
+//
+// Test functions.
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_journal::storage::Backend;
+
+    /// An app on a new journal with the given tasks (with IDs from 1 on).
+    fn new_app(dir: &tempfile::TempDir, texts: &[&str]) -> App {
+        let journal = Journal::new(dir.path().join("journal.json"), Backend::Json);
+        for text in texts {
+            tasks::add_task(&journal, Task::new(text.to_string())).unwrap();
+        }
+        App::new(journal, ListOptions::default())
+    }
+
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        for &key in keys {
+            app.handle_key(KeyEvent::from(key));
+        }
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, &[KeyCode::Char(c)]);
+        }
+    }
+
+    fn ids(app: &App) -> Vec<u64> {
+        app.entries.iter().map(|entry| entry.task.id).collect()
+    }
+
+    fn selected_id(app: &App) -> Option<u64> {
+        app.selected().map(|task| task.id)
+    }
+
+    #[test]
+    fn priorities() {
+        use Priority::*;
+        let mut priority = None;
+        for expected in [Some(Low), Some(Medium), Some(High), Some(High)] {
+            priority = shift_priority(priority, true);
+            assert_eq!(priority, expected);
+        }
+        for expected in [Some(Medium), Some(Low), None, None] {
+            priority = shift_priority(priority, false);
+            assert_eq!(priority, expected);
+        }
+    }
+
+    #[test]
+    fn moving_the_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = new_app(&dir, &["one", "two", "three"]);
+        assert_eq!(selected_id(&app), Some(1));
+
+        press(&mut app, &[KeyCode::Up]);
+        assert_eq!(selected_id(&app), Some(1));
+        press(&mut app, &[KeyCode::Down, KeyCode::Char('j')]);
+        assert_eq!(selected_id(&app), Some(3));
+        press(&mut app, &[KeyCode::Down, KeyCode::PageDown]);
+        assert_eq!(selected_id(&app), Some(3));
+        press(&mut app, &[KeyCode::Home]);
+        assert_eq!(selected_id(&app), Some(1));
+        press(&mut app, &[KeyCode::End, KeyCode::PageUp]);
+        assert_eq!(selected_id(&app), Some(1));
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = new_app(&dir, &[]);
+        press(&mut app, &[KeyCode::Down, KeyCode::End]);
+        assert_eq!(app.list_state.selected(), None);
+    }
+
+    #[test]
+    fn adding_and_editing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = new_app(&dir, &["Water the plants"]);
+
+        press(&mut app, &[KeyCode::Char('a')]);
+        type_text(&mut app, "Pay rent +home !high");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.status, "Added task 2");
+        assert_eq!(selected_id(&app), Some(2));
+        let task = app.selected().unwrap();
+        assert_eq!(task.text, "Pay rent");
+        assert_eq!(task.priority, Some(Priority::High));
+        assert!(task.tags.contains("home"));
+
+        // The text being edited starts as the task's.
+        press(&mut app, &[KeyCode::Char('e'), KeyCode::Backspace]);
+        type_text(&mut app, "s");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.selected().unwrap().text, "Pay rens");
+
+        // Escape cancels the prompt.
+        press(&mut app, &[KeyCode::Char('a')]);
+        type_text(&mut app, "Call mom");
+        press(&mut app, &[KeyCode::Esc]);
+        assert_eq!(app.input, None);
+        assert_eq!(ids(&app), vec![1, 2]);
+        assert!(!app.quit);
+
+        press(&mut app, &[KeyCode::Char('-'), KeyCode::Char('-')]);
+        assert_eq!(app.selected().unwrap().priority, Some(Priority::Low));
+        assert_eq!(app.status, "Task 2 has priority low");
+    }
+
+    #[test]
+    fn completing_keeps_a_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = new_app(&dir, &["one", "two", "three"]);
+
+        press(&mut app, &[KeyCode::End, KeyCode::Char('d')]);
+        assert_eq!(ids(&app), vec![1, 2]);
+        assert_eq!(selected_id(&app), Some(2));
+
+        // Showing completed tasks keeps the selected one.
+        press(&mut app, &[KeyCode::Char('c')]);
+        assert_eq!(ids(&app), vec![1, 2, 3]);
+        assert_eq!(selected_id(&app), Some(2));
+
+        press(&mut app, &[KeyCode::Char('u'), KeyCode::Char('q')]);
+        assert_eq!(app.status, "Reverted the last change");
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = new_app(&dir, &["Pay rent", "Water the plants"]);
+        tasks::edit_task(&app.journal, 2, |task| {
+            task.tags.insert("home".to_owned());
+        })
+        .unwrap();
+
+        press(&mut app, &[KeyCode::Char('/')]);
+        type_text(&mut app, "RENT");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(ids(&app), vec![1]);
+
+        // A tag filter replaces the text one, and the other way around.
+        press(&mut app, &[KeyCode::Char('/')]);
+        assert_eq!(app.input, Some((Prompt::Filter, "RENT".to_owned())));
+        press(&mut app, &[KeyCode::Backspace; 4]);
+        type_text(&mut app, "+home");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(
+            (app.options.tag.as_deref(), &app.query[..]),
+            (Some("home"), "")
+        );
+        assert_eq!(ids(&app), vec![2]);
+
+        press(&mut app, &[KeyCode::Char('/')]);
+        type_text(&mut app, "plants");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(
+            (app.options.tag.as_deref(), &app.query[..]),
+            (None, "plants")
+        );
+        assert_eq!(ids(&app), vec![2]);
+
+        // A lone `+` is text.
+        press(&mut app, &[KeyCode::Char('/')]);
+        press(&mut app, &[KeyCode::Backspace; 6]);
+        type_text(&mut app, "+");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.query, "+");
+        assert!(ids(&app).is_empty());
+    }
+}